@@ -0,0 +1,294 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap},
+    env, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use image::{imageops, RgbaImage};
+use puzzle_pawz::{
+    gamelogic::{
+        level_mgr::{ManagedLevel, TILE_SIZE},
+        validation::validate_level,
+    },
+    tilemap::{TileSet, Tilemap, Tiles},
+};
+
+const ASSET_DIR: &str = "assets";
+// Tiled stores flips and rotation in the upper bits of a tile id
+const TMX_FLAG_MASK: u32 = 0x1fff_ffff;
+
+const USAGE: &str = "Usage: pawz-tool <command>
+
+Commands:
+  validate                       check the data of every level
+  colliders <walls.csv>          print map colliders for a walls layer
+  convert <tilemap.tmx> [dir]    write the layers of a Tiled map as csv files
+  tiles                          list the tiles used per level
+  preview <level> <output.png>   render all tile layers of a level (1, 2, ...)";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let result = match args.as_slice() {
+        ["validate"] => validate(),
+        ["colliders", walls] => colliders(Path::new(walls)),
+        ["convert", tmx] => {
+            let tmx = Path::new(tmx);
+            convert(tmx, tmx.parent().unwrap_or(Path::new(".")))
+        }
+        ["convert", tmx, directory] => convert(Path::new(tmx), Path::new(directory)),
+        ["tiles"] => tiles(),
+        ["preview", level, output] => {
+            parse_level(level).and_then(|level| preview(level, Path::new(output)))
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_level(number: &str) -> Result<ManagedLevel, String> {
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| ManagedLevel::levels().nth(number.checked_sub(1)?))
+        .ok_or(format!("There is no level {}", number))
+}
+
+fn load_tile_set(path: &str) -> Result<TileSet, String> {
+    TileSet::from_path(Path::new(ASSET_DIR).join(path))
+        .ok_or(format!("Could not load tileset {}", path))
+}
+
+fn load_layers(level: ManagedLevel) -> Result<Vec<Tiles>, String> {
+    level
+        .get_data()
+        .tilemap_layers
+        .iter()
+        .map(|layer| {
+            Tiles::from_path(Path::new(ASSET_DIR).join(layer))
+                .ok_or(format!("Could not load layer {}", layer))
+        })
+        .collect()
+}
+
+fn validate() -> Result<(), String> {
+    let mut issue_count = 0;
+    for level in ManagedLevel::levels() {
+        let data = level.get_data();
+        let tile_set = load_tile_set(&data.tileset)?;
+        let layers = load_layers(level)?;
+        let ground = layers
+            .first()
+            .and_then(|tiles| Tilemap::new(&tile_set, tiles))
+            .ok_or(format!("{:?} has no ground layer", level))?;
+        let issues = validate_level(data, &ground);
+        for issue in &issues {
+            println!("{:?}: {}", level, issue);
+        }
+        issue_count += issues.len();
+    }
+    if issue_count > 0 {
+        return Err(format!("Found {} issues", issue_count));
+    }
+    println!("All levels are fine");
+    Ok(())
+}
+
+// (corner offset, size) in tiles
+type WallCollider = ((f32, f32), (f32, f32));
+
+fn wall_colliders(tile: usize) -> &'static [WallCollider] {
+    match tile {
+        // unfinished & incorrect colliders
+        11 => &[((-0.1, -0.1), (1.0, 0.8)), ((-0.1, 0.4), (0.8, 0.2))],
+        12 => &[((0.1, -0.1), (0.8, 0.8))],
+        13 => &[((-0.1, 0.1), (0.8, 0.8))],
+        14 => &[((0.1, 0.1), (0.8, 0.8))],
+        15 => &[((-0.1, -0.1), (0.8, 0.8))],
+        16 => &[((0.1, -0.1), (0.8, 0.8))],
+        17 => &[((-0.1, 0.0), (0.8, 1.0))],
+        18 => &[((0.0, 0.1), (1.0, 0.8))],
+        19 => &[((0.0, -0.1), (1.0, 0.8))],
+        20 => &[((0.1, 0.0), (0.8, 1.0))],
+        _ => &[],
+    }
+}
+
+fn colliders(walls: &Path) -> Result<(), String> {
+    let walls =
+        Tiles::from_path(walls).ok_or(format!("Could not load layer {}", walls.display()))?;
+    for x in 0..walls.width() {
+        for y in 0..walls.height() {
+            let Some(tile) = walls.get(x, y) else {
+                continue;
+            };
+            for ((offset_x, offset_y), (width, height)) in wall_colliders(tile) {
+                println!(
+                    "SolidColliderData {{whitelisted: None, corner_position: Vec2::new({:.1} * TILE_SIZE, {:.1} * TILE_SIZE), size: Vec2::new({:.1} * TILE_SIZE, {:.1} * TILE_SIZE)}},",
+                    x as f32 + offset_x,
+                    y as f32 + offset_y,
+                    width,
+                    height
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let length = tag[start..].find('"')?;
+    Some(&tag[start..start + length])
+}
+
+// tile ids by position
+type TmxTiles = HashMap<(i64, i64), u32>;
+
+// Only the csv encoding of infinite maps is supported, which is what the levels use.
+// Tiled exports the bounding box of the tiles in all layers, so the same is done here.
+fn convert(tmx: &Path, directory: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(tmx).map_err(|err| err.to_string())?;
+    let first_gid: u32 = content
+        .find("<tileset ")
+        .and_then(|start| attribute(&content[start..], "firstgid"))
+        .and_then(|gid| gid.parse().ok())
+        .unwrap_or(1);
+    let mut layers: Vec<(String, TmxTiles)> = Vec::new();
+    for layer in content.split("<layer").skip(1) {
+        let name = attribute(layer, "name").ok_or("Layer without a name".to_string())?;
+        let mut tiles = HashMap::new();
+        for chunk in layer.split("<chunk").skip(1) {
+            let number = |key: &str| {
+                attribute(chunk, key)
+                    .and_then(|value| value.parse::<i64>().ok())
+                    .ok_or(format!("Chunk in layer {} without {}", name, key))
+            };
+            let (x, y, width) = (number("x")?, number("y")?, number("width")?);
+            let data_start = chunk.find('>').ok_or("Unterminated chunk".to_string())? + 1;
+            let data_end = chunk
+                .find("</chunk>")
+                .ok_or("Unterminated chunk".to_string())?;
+            let gids = chunk[data_start..data_end]
+                .split(',')
+                .map(|gid| gid.trim().parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|_| format!("Layer {} is not csv encoded", name))?;
+            for (i, gid) in gids.into_iter().enumerate() {
+                let gid = gid & TMX_FLAG_MASK;
+                if gid >= first_gid {
+                    let i = i as i64;
+                    tiles.insert((x + i % width, y + i / width), gid - first_gid);
+                }
+            }
+        }
+        layers.push((name.to_string(), tiles));
+    }
+    let positions = || layers.iter().flat_map(|(_, tiles)| tiles.keys());
+    let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) = (
+        positions().map(|(x, _)| *x).min(),
+        positions().map(|(x, _)| *x).max(),
+        positions().map(|(_, y)| *y).min(),
+        positions().map(|(_, y)| *y).max(),
+    ) else {
+        return Err("The map is empty".to_string());
+    };
+    for (name, tiles) in &layers {
+        let mut csv = String::new();
+        for y in min_y..=max_y {
+            let row: Vec<String> = (min_x..=max_x)
+                .map(|x| {
+                    tiles
+                        .get(&(x, y))
+                        .map_or("-1".to_string(), |tile| tile.to_string())
+                })
+                .collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        let path: PathBuf = directory.join(format!("tilemap_{}.csv", name));
+        fs::write(&path, csv).map_err(|err| format!("{}: {}", path.display(), err))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn tiles() -> Result<(), String> {
+    for level in ManagedLevel::levels() {
+        let tile_set = load_tile_set(&level.get_data().tileset)?;
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for tiles in load_layers(level)? {
+            let Some(tilemap) = Tilemap::new(&tile_set, &tiles) else {
+                continue;
+            };
+            for y in 0..tilemap.height() {
+                for x in 0..tilemap.width() {
+                    if let Some(texture) = tilemap.get_texture_name_of_tile(x, y) {
+                        *counts.entry(texture.to_string()).or_default() += 1;
+                    }
+                }
+            }
+        }
+        println!("{:?}:", level);
+        for (texture, count) in counts {
+            println!("  {:5} {}", count, texture);
+        }
+    }
+    Ok(())
+}
+
+fn preview(level: ManagedLevel, output: &Path) -> Result<(), String> {
+    let tile_set = load_tile_set(&level.get_data().tileset)?;
+    let layers = load_layers(level)?;
+    let tilemaps: Vec<Tilemap> = layers
+        .iter()
+        .filter_map(|tiles| Tilemap::new(&tile_set, tiles))
+        .collect();
+    let width = tilemaps.iter().map(|tilemap| tilemap.width()).max();
+    let height = tilemaps.iter().map(|tilemap| tilemap.height()).max();
+    let (Some(width), Some(height)) = (width, height) else {
+        return Err(format!("{:?} has no tiles", level));
+    };
+    let tile_size = TILE_SIZE as u32;
+    let mut canvas = RgbaImage::new(width as u32 * tile_size, height as u32 * tile_size);
+    let mut textures: BTreeMap<String, RgbaImage> = BTreeMap::new();
+    // later layers are drawn on top, like in the game
+    for tilemap in &tilemaps {
+        for y in 0..tilemap.height() {
+            for x in 0..tilemap.width() {
+                let Some(name) = tilemap.get_texture_name_of_tile(x, y) else {
+                    continue;
+                };
+                let texture = match textures.entry(name.to_string()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let texture = image::open(Path::new(ASSET_DIR).join(name))
+                            .map_err(|err| format!("{}: {}", name, err))?;
+                        entry.insert(texture.to_rgba8())
+                    }
+                };
+                imageops::overlay(
+                    &mut canvas,
+                    texture,
+                    (x as u32 * tile_size) as i64,
+                    (y as u32 * tile_size) as i64,
+                );
+            }
+        }
+    }
+    canvas
+        .save(output)
+        .map_err(|err| format!("{}: {}", output.display(), err))?;
+    println!("Wrote {}", output.display());
+    Ok(())
+}
@@ -0,0 +1,590 @@
+use bevy::{input::InputSystem, prelude::*, utils::HashSet};
+
+use crate::{
+    gamelogic::{
+        character::{
+            trigger_push_buttons, Character, CurrentCharacter, DiscoveredCharacters, NoClip,
+        },
+        goal::LevelGoal,
+        ground::Movement,
+        level::PushButton,
+        level_mgr::{LevelManager, ManagedLevel, TILE_SIZE},
+        navigation::NavigationPath,
+        rating::LevelStats,
+        signal::update_signals,
+    },
+    GameState,
+};
+
+const MAX_OUTPUT_LINES: usize = 12;
+const COMMANDS: [(&str, &str); 9] = [
+    ("help", "help"),
+    ("level", "level <n>"),
+    ("discover", "discover <character>"),
+    ("tp", "tp <x> <y> (in tiles)"),
+    ("press", "press <button index>"),
+    ("noclip", "noclip"),
+    ("timescale", "timescale <factor>"),
+    ("reload", "reload"),
+    ("win", "win"),
+];
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    output: Vec<String>,
+    history: Vec<String>,
+    history_index: Option<usize>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        self.output.push(line.into());
+    }
+}
+
+// buttons held down by the console until `press` is used on them again
+#[derive(Resource, Default)]
+struct ForcedButtons {
+    indices: HashSet<usize>,
+}
+
+#[derive(Event, Debug, PartialEq)]
+enum ConsoleCommand {
+    Help,
+    Level(ManagedLevel),
+    Discover(Character),
+    Teleport(Vec2),
+    Press(usize),
+    NoClip,
+    TimeScale(f32),
+    Reload,
+    Win,
+}
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleText;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>();
+        app.init_resource::<ForcedButtons>();
+        app.add_event::<ConsoleCommand>();
+        app.add_systems(OnEnter(GameState::InGame), spawn_console);
+        app.add_systems(OnExit(GameState::InGame), despawn_console);
+        // typing must not reach the game, so the keys are consumed before `Update`
+        app.add_systems(
+            PreUpdate,
+            console_input
+                .after(InputSystem)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            (run_commands, mark_scaled_runs, update_console).run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            force_buttons
+                .after(trigger_push_buttons)
+                .before(update_signals)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn spawn_console(mut commands: Commands, asset_server: Res<AssetServer>, console: Res<Console>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: if console.open {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    },
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    bottom: Val::Px(0.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ConsoleRoot,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/NotoSans-Regular.ttf"),
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                ConsoleText,
+            ));
+        });
+}
+
+fn despawn_console(mut commands: Commands, roots: Query<Entity, With<ConsoleRoot>>) {
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(argument: Option<&str>, name: &str) -> Result<T, String> {
+    let argument = argument.ok_or(format!("Missing {}", name))?;
+    argument
+        .parse()
+        .map_err(|_| format!("Invalid {}: {}", name, argument))
+}
+
+fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("Empty command".to_string());
+    };
+    let command = match name {
+        "help" => ConsoleCommand::Help,
+        "level" => {
+            let number: usize = parse_number(words.next(), "level number")?;
+            number
+                .checked_sub(1)
+                .and_then(|index| ManagedLevel::levels().nth(index))
+                .map(ConsoleCommand::Level)
+                .ok_or(format!("There is no level {}", number))?
+        }
+        "discover" => {
+            let name = words.next().ok_or("Missing character".to_string())?;
+            Character::from_name(name)
+                .map(ConsoleCommand::Discover)
+                .ok_or(format!("Unknown character: {}", name))?
+        }
+        "tp" => {
+            let x: f32 = parse_number(words.next(), "x")?;
+            let y: f32 = parse_number(words.next(), "y")?;
+            ConsoleCommand::Teleport(Vec2::new(x * TILE_SIZE, -y * TILE_SIZE))
+        }
+        "press" => ConsoleCommand::Press(parse_number(words.next(), "button index")?),
+        "noclip" => ConsoleCommand::NoClip,
+        "timescale" => {
+            let scale: f32 = parse_number(words.next(), "factor")?;
+            if !scale.is_finite() || scale < 0.0 {
+                return Err(format!("Invalid factor: {}", scale));
+            }
+            ConsoleCommand::TimeScale(scale)
+        }
+        "reload" => ConsoleCommand::Reload,
+        "win" => ConsoleCommand::Win,
+        _ => return Err(format!("Unknown command: {}", name)),
+    };
+    match words.next() {
+        Some(extra) => Err(format!("Unexpected argument: {}", extra)),
+        None => Ok(command),
+    }
+}
+
+// returns where the word under completion starts and all fitting replacements for it
+fn completions(input: &str) -> (usize, Vec<String>) {
+    let start = input.rfind(' ').map_or(0, |space| space + 1);
+    let prefix = &input[start..];
+    let options: Vec<String> = if start == 0 {
+        COMMANDS.iter().map(|(name, _)| name.to_string()).collect()
+    } else {
+        match input.split_whitespace().next() {
+            Some("discover") => Character::ALL
+                .iter()
+                .map(|character| character.name().to_lowercase())
+                .collect(),
+            Some("level") => (1..=ManagedLevel::levels().count())
+                .map(|number| number.to_string())
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    let options = options
+        .into_iter()
+        .filter(|option| option.starts_with(prefix))
+        .collect();
+    (start, options)
+}
+
+fn common_prefix(options: &[String]) -> String {
+    let Some(first) = options.first() else {
+        return String::new();
+    };
+    // in bytes of `first`, always on a character boundary
+    let mut length = first.len();
+    for option in options {
+        length = first
+            .char_indices()
+            .zip(option.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(first.len().min(option.len()), |((index, _), _)| index)
+            .min(length);
+    }
+    first[..length].to_string()
+}
+
+fn console_input(
+    mut console: ResMut<Console>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut received: EventReader<ReceivedCharacter>,
+    mut commands: EventWriter<ConsoleCommand>,
+) {
+    if keys.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+        keys.reset_all();
+        received.clear();
+        return;
+    }
+    if !console.open {
+        received.clear();
+        return;
+    }
+    for event in received.iter() {
+        if !event.char.is_control() && event.char != '`' {
+            console.input.push(event.char);
+            console.history_index = None;
+        }
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        console.open = false;
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        let (start, options) = completions(&console.input);
+        match options.as_slice() {
+            [] => {}
+            [option] => {
+                console.input = format!("{}{} ", &console.input[..start], option);
+            }
+            options => {
+                let prefix = common_prefix(options);
+                console.input = format!("{}{}", &console.input[..start], prefix);
+                console.print(options.join("  "));
+            }
+        }
+    }
+    if keys.just_pressed(KeyCode::Up) && !console.history.is_empty() {
+        let index = console
+            .history_index
+            .map_or(console.history.len() - 1, |index| index.saturating_sub(1));
+        console.history_index = Some(index);
+        console.input = console.history[index].clone();
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        if let Some(index) = console.history_index {
+            if index + 1 < console.history.len() {
+                console.history_index = Some(index + 1);
+                console.input = console.history[index + 1].clone();
+            } else {
+                console.history_index = None;
+                console.input.clear();
+            }
+        }
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        let line = console.input.trim().to_string();
+        console.input.clear();
+        console.history_index = None;
+        if !line.is_empty() {
+            console.print(format!("> {}", line));
+            if console.history.last() != Some(&line) {
+                console.history.push(line.clone());
+            }
+            match parse_command(&line) {
+                Ok(command) => commands.send(command),
+                Err(err) => console.print(err),
+            }
+        }
+    }
+    // the game only sees the keyboard while the console is closed
+    keys.reset_all();
+}
+
+fn run_commands(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    mut forced: ResMut<ForcedButtons>,
+    mut time: ResMut<Time>,
+    asset_server: Res<AssetServer>,
+    mut events: EventReader<ConsoleCommand>,
+    mut manager: Query<&mut LevelManager>,
+    mut player_query: Query<(&CurrentCharacter, &mut DiscoveredCharacters)>,
+    mut characters: Query<(
+        Entity,
+        &Character,
+        &mut Transform,
+        &mut Movement,
+        Option<&mut NavigationPath>,
+        Option<&NoClip>,
+    )>,
+    mut buttons: Query<(&mut PushButton, &mut Handle<Image>)>,
+    mut goal: Query<&mut LevelGoal>,
+    mut stats: Query<&mut LevelStats>,
+) {
+    for command in events.iter() {
+        if matches!(
+            command,
+            ConsoleCommand::NoClip | ConsoleCommand::TimeScale(_) | ConsoleCommand::Win
+        ) {
+            for mut stats in &mut stats {
+                stats.used_console = true;
+            }
+        }
+        match command {
+            ConsoleCommand::Help => {
+                for (_, usage) in COMMANDS {
+                    console.print(usage);
+                }
+            }
+            ConsoleCommand::Level(level) => {
+                let Ok(mut manager) = manager.get_single_mut() else {
+                    continue;
+                };
+                manager.next = Some(*level);
+                forced.indices.clear();
+                console.print(format!("Loading {:?}", level));
+            }
+            ConsoleCommand::Reload => {
+                let Ok(mut manager) = manager.get_single_mut() else {
+                    continue;
+                };
+                manager.next = manager.current;
+                forced.indices.clear();
+                console.print("Reloading the level");
+            }
+            ConsoleCommand::Discover(character) => {
+                let Ok((_, mut discovered)) = player_query.get_single_mut() else {
+                    continue;
+                };
+                if discovered.discovered.contains(character) {
+                    console.print(format!("{:?} is already discovered", character));
+                } else if !characters.iter().any(|(_, other, ..)| other == character) {
+                    console.print(format!("{:?} is not in this level", character));
+                } else {
+                    discovered.discovered.push(character.clone());
+                    console.print(format!("Discovered {:?}", character));
+                }
+            }
+            ConsoleCommand::Teleport(position) => {
+                let Ok((current, _)) = player_query.get_single() else {
+                    continue;
+                };
+                for (_, character, mut transform, mut movement, path, _) in &mut characters {
+                    if *character != current.current {
+                        continue;
+                    }
+                    transform.translation.x = position.x;
+                    transform.translation.y = position.y;
+                    movement.velocity = Vec2::ZERO;
+                    if let Some(mut path) = path {
+                        path.waypoints.clear();
+                    }
+                    console.print(format!("Teleported {:?}", character));
+                }
+            }
+            ConsoleCommand::Press(index) => {
+                let held = if forced.indices.remove(index) {
+                    false
+                } else {
+                    forced.indices.insert(*index);
+                    true
+                };
+                let mut found = false;
+                for (mut button, mut texture) in &mut buttons {
+                    if button.index == *index {
+                        found = true;
+                        button.pressed = held;
+                        *texture = asset_server.load(button.kind.texture(held));
+                    }
+                }
+                if !found {
+                    forced.indices.remove(index);
+                    console.print(format!("There is no button {}", index));
+                } else if held {
+                    console.print(format!("Holding button {}", index));
+                } else {
+                    console.print(format!("Released button {}", index));
+                }
+            }
+            ConsoleCommand::NoClip => {
+                let Ok((current, _)) = player_query.get_single() else {
+                    continue;
+                };
+                for (entity, character, _, _, _, no_clip) in &characters {
+                    if *character != current.current {
+                        continue;
+                    }
+                    if no_clip.is_some() {
+                        commands.entity(entity).remove::<NoClip>();
+                        console.print(format!("Noclip off for {:?}", character));
+                    } else {
+                        commands.entity(entity).insert(NoClip);
+                        console.print(format!("Noclip on for {:?}", character));
+                    }
+                }
+            }
+            ConsoleCommand::TimeScale(scale) => {
+                time.set_relative_speed(*scale);
+                console.print(format!("Time scale is {}", scale));
+            }
+            ConsoleCommand::Win => {
+                let Ok(mut goal) = goal.get_single_mut() else {
+                    continue;
+                };
+                goal.unmet.clear();
+                goal.completed = true;
+                console.print("Level completed");
+            }
+        }
+    }
+}
+
+// the time scale outlasts the level it was set in
+fn mark_scaled_runs(time: Res<Time>, mut stats: Query<&mut LevelStats, Added<LevelStats>>) {
+    if time.relative_speed() == 1.0 {
+        return;
+    }
+    for mut stats in &mut stats {
+        stats.used_console = true;
+    }
+}
+
+fn force_buttons(
+    asset_server: Res<AssetServer>,
+    mut forced: ResMut<ForcedButtons>,
+    loaded: Query<(), Changed<LevelManager>>,
+    mut buttons: Query<(&mut PushButton, &mut Handle<Image>)>,
+) {
+    // button indices only mean something within one level
+    if !loaded.is_empty() {
+        forced.indices.clear();
+    }
+    for (mut button, mut texture) in &mut buttons {
+        if forced.indices.contains(&button.index) && !button.pressed {
+            button.pressed = true;
+            *texture = asset_server.load(button.kind.texture(true));
+        }
+    }
+}
+
+fn update_console(
+    console: Res<Console>,
+    mut roots: Query<&mut Style, With<ConsoleRoot>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for mut style in &mut roots {
+        style.display = if console.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    let skipped = console.output.len().saturating_sub(MAX_OUTPUT_LINES);
+    let mut lines: Vec<&str> = console.output[skipped..]
+        .iter()
+        .map(|line| line.as_str())
+        .collect();
+    let prompt = format!("> {}_", console.input);
+    lines.push(&prompt);
+    for mut text in &mut texts {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(
+            parse_command("level 2"),
+            Ok(ConsoleCommand::Level(ManagedLevel::Level2))
+        );
+        assert_eq!(
+            parse_command("  discover RABBIT "),
+            Ok(ConsoleCommand::Discover(Character::Rabbit))
+        );
+        assert_eq!(
+            parse_command("tp 3 4"),
+            Ok(ConsoleCommand::Teleport(Vec2::new(3.0, -4.0) * TILE_SIZE))
+        );
+        assert_eq!(
+            parse_command("timescale 0.5"),
+            Ok(ConsoleCommand::TimeScale(0.5))
+        );
+    }
+
+    #[test]
+    fn bad_commands_explain_the_error() {
+        for (line, error) in [
+            ("", "Empty command"),
+            ("fly", "Unknown command: fly"),
+            ("level", "Missing level number"),
+            ("level two", "Invalid level number: two"),
+            ("level 0", "There is no level 0"),
+            ("discover cat", "Unknown character: cat"),
+            ("tp 1", "Missing y"),
+            ("press -1", "Invalid button index: -1"),
+            ("timescale -1", "Invalid factor: -1"),
+            ("win now", "Unexpected argument: now"),
+        ] {
+            assert_eq!(parse_command(line), Err(error.to_string()), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn words_are_completed() {
+        assert_eq!(completions("ti"), (0, vec!["timescale".to_string()]));
+        assert_eq!(
+            completions("discover "),
+            (
+                9,
+                vec!["turtle", "rabbit", "crocodile", "lizard"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            )
+        );
+        assert_eq!(completions("discover l"), (9, vec!["lizard".to_string()]));
+        assert_eq!(completions("level 4"), (6, Vec::new()));
+        assert_eq!(completions("tp "), (3, Vec::new()));
+    }
+
+    #[test]
+    fn common_prefix_stops_at_the_first_difference() {
+        let prefix = |options: &[&str]| {
+            common_prefix(
+                &options
+                    .iter()
+                    .map(|option| option.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(prefix(&[]), "");
+        assert_eq!(prefix(&["reload"]), "reload");
+        assert_eq!(prefix(&["timescale", "tp"]), "t");
+        assert_eq!(prefix(&["level", "lev"]), "lev");
+        assert_eq!(prefix(&["grün", "größe"]), "gr");
+        assert_eq!(prefix(&["größe", "größer"]), "größe");
+    }
+}
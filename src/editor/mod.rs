@@ -0,0 +1,1203 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::{
+    gamelogic::{
+        character::Character,
+        level::{ActuatorKind, SwitchKind},
+        level_mgr::{
+            BridgeData, ButtonData, CharacterData, CharacterFlagData, LevelData, LevelManager,
+            LevelOverrides, LoadedLevel, ManagedLevel, SolidColliderData, TILE_SIZE,
+        },
+        validation::validate_level,
+    },
+    loading::TilemapAtlas,
+    tilemap::{TileSet, Tilemap, Tiles},
+    GameState, MainCamera,
+};
+
+mod source;
+
+const PAN_SPEED: f32 = 320.0;
+const ZOOM_STEP: f32 = 1.25;
+const PICK_RADIUS: f32 = 12.0;
+const OBJECT_SNAP: f32 = TILE_SIZE / 2.0;
+const COLLIDER_SNAP: f32 = TILE_SIZE / 10.0;
+const LABEL_SIZE: f32 = 8.0;
+const COLORS: [Color; 4] = [
+    Color::rgb(0.8, 0.2, 0.2),
+    Color::rgb(0.2, 0.8, 0.2),
+    Color::rgb(0.2, 0.2, 0.8),
+    Color::rgb(0.8, 0.8, 0.2),
+];
+const HELP: &str = "1 tiles  2 objects  3 colliders  Tab layer  PgUp/PgDn level  WASD pan  Z/X zoom
+Q/E brush, index, whitelist or flag owner  C colour  N negated  Del remove
+B button  G bridge  P character  F character flag
+F5 playtest  Ctrl+S save  Esc menu";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tool {
+    Tiles,
+    Objects,
+    Colliders,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Selection {
+    Flag,
+    CharacterFlag(usize),
+    Character(usize),
+    Button(usize),
+    Bridge(usize),
+    Collider(usize),
+}
+
+impl Selection {
+    fn position(&self, data: &LevelData) -> Option<Vec2> {
+        match *self {
+            Selection::Flag => Some(data.flag_position),
+            Selection::CharacterFlag(i) => data.character_flags.get(i).map(|flag| flag.position),
+            Selection::Character(i) => data
+                .characters
+                .get(i)
+                .map(|character| character.starting_position),
+            Selection::Button(i) => data.buttons.get(i).map(|button| button.position),
+            Selection::Bridge(i) => data.bridges.get(i).map(|bridge| bridge.position),
+            Selection::Collider(i) => data
+                .map_colliders
+                .get(i)
+                .map(|collider| collider.corner_position),
+        }
+    }
+
+    fn set_position(&self, data: &mut LevelData, position: Vec2) {
+        match *self {
+            Selection::Flag => data.flag_position = position,
+            Selection::CharacterFlag(i) => data.character_flags[i].position = position,
+            Selection::Character(i) => data.characters[i].starting_position = position,
+            Selection::Button(i) => data.buttons[i].position = position,
+            Selection::Bridge(i) => data.bridges[i].position = position,
+            Selection::Collider(i) => data.map_colliders[i].corner_position = position,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Editor {
+    level: ManagedLevel,
+    data: Option<LevelData>,
+    layers: Vec<Tiles>,
+    palette: Vec<(usize, String)>,
+    tool: Tool,
+    layer: usize,
+    brush: usize,
+    selection: Option<Selection>,
+    dragging: bool,
+    collider_start: Option<Vec2>,
+    tiles_changed: bool,
+    objects_changed: bool,
+    unsaved: bool,
+    playtesting: bool,
+    // why the level could not be loaded or saved, shown until the next load or save
+    error: Option<String>,
+}
+
+impl Editor {
+    fn brush_tile(&self) -> Option<&(usize, String)> {
+        self.palette.get(self.brush)
+    }
+
+    fn texture_name(&self, tile: usize) -> Option<&str> {
+        self.palette
+            .iter()
+            .find(|(tile_type, _)| *tile_type == tile)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+#[derive(Component)]
+struct EditorTile {
+    layer: usize,
+    x: usize,
+    y: usize,
+}
+
+#[derive(Component)]
+struct EditorObject;
+
+#[derive(Component)]
+struct EditorUi;
+
+#[derive(Component)]
+struct EditorStatus;
+
+#[derive(Component)]
+struct BrushPreview;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Editor {
+            level: ManagedLevel::Level1,
+            data: None,
+            layers: Vec::new(),
+            palette: Vec::new(),
+            tool: Tool::Tiles,
+            layer: 0,
+            brush: 0,
+            selection: None,
+            dragging: false,
+            collider_start: None,
+            tiles_changed: false,
+            objects_changed: false,
+            unsaved: false,
+            playtesting: false,
+            error: None,
+        })
+        .add_systems(OnEnter(GameState::Editor), enter_editor)
+        .add_systems(OnExit(GameState::Editor), exit_editor)
+        .add_systems(
+            Update,
+            (
+                switch_tools,
+                move_editor_camera,
+                edit_selection,
+                paint_tiles,
+                edit_objects,
+                edit_colliders,
+                playtest,
+                save_level,
+                spawn_editor_tiles,
+                spawn_editor_objects,
+                draw_editor_gizmos,
+                update_editor_status,
+            )
+                .chain()
+                .run_if(in_state(GameState::Editor)),
+        )
+        .add_systems(Update, return_to_editor.run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn to_world(position: Vec2) -> Vec2 {
+    Vec2::new(position.x, -position.y)
+}
+
+fn snap(position: Vec2, step: f32) -> Vec2 {
+    (position / step).round() * step
+}
+
+fn collider_bounds(collider: &SolidColliderData) -> Rect {
+    let min = collider.corner_position - TILE_SIZE / 2.0;
+    Rect::from_corners(min, min + collider.size)
+}
+
+fn next_color(color: Color) -> Color {
+    let index = COLORS
+        .iter()
+        .position(|candidate| *candidate == color)
+        .map_or(0, |index| (index + 1) % COLORS.len());
+    COLORS[index]
+}
+
+// level pixels under the mouse
+fn cursor_position(
+    windows: &Query<&Window>,
+    camera: &Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) -> Option<Vec2> {
+    let screen_position = windows.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = camera.get_single().ok()?;
+    camera
+        .viewport_to_world_2d(camera_transform, screen_position)
+        .map(to_world)
+}
+
+fn load_editor_level(
+    editor: &mut Editor,
+    overrides: &LevelOverrides,
+    tiles: &Assets<Tiles>,
+    tilesets: &Assets<TileSet>,
+    asset_server: &AssetServer,
+) {
+    let data = overrides
+        .levels
+        .get(&editor.level)
+        .cloned()
+        .unwrap_or_else(|| editor.level.get_data().clone());
+    editor.selection = None;
+    editor.dragging = false;
+    editor.collider_start = None;
+    editor.tiles_changed = true;
+    editor.objects_changed = true;
+    editor.unsaved = false;
+    editor.error = None;
+    let layers: Option<Vec<Tiles>> = data
+        .tilemap_layers
+        .iter()
+        .map(|layer| tiles.get(&asset_server.get_handle(layer.as_str())).cloned())
+        .collect();
+    // without every layer the edits would be saved into the wrong files
+    let Some(layers) = layers else {
+        error!("Not all tile layers of {:?} are loaded", editor.level);
+        editor.data = None;
+        editor.layers = Vec::new();
+        editor.error =
+            Some("Not all tile layers are loaded, the level can't be edited".to_string());
+        return;
+    };
+    editor.layers = layers;
+    editor.palette = tilesets
+        .get(&asset_server.get_handle(data.tileset.as_str()))
+        .map(|tile_set| {
+            tile_set
+                .texture_names()
+                .map(|(tile_type, name)| (tile_type, name.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    editor.data = Some(data);
+    editor.layer = 0;
+    editor.brush = editor.brush.min(editor.palette.len().saturating_sub(1));
+}
+
+// makes the game load the edited level instead of the built-in data
+fn apply_edits(
+    editor: &Editor,
+    overrides: &mut LevelOverrides,
+    tiles: &mut Assets<Tiles>,
+    asset_server: &AssetServer,
+) {
+    let Some(data) = &editor.data else {
+        return;
+    };
+    for (path, layer) in data.tilemap_layers.iter().zip(&editor.layers) {
+        let handle: Handle<Tiles> = asset_server.get_handle(path.as_str());
+        tiles.set_untracked(handle.id(), layer.clone());
+    }
+    overrides.levels.insert(editor.level, data.clone());
+}
+
+fn focus_camera(editor: &Editor, camera: &mut Query<&mut Transform, With<MainCamera>>) {
+    let Some(data) = &editor.data else {
+        return;
+    };
+    let focus = data
+        .characters
+        .iter()
+        .find(|character| character.character == data.starting_character)
+        .map_or(data.flag_position, |character| character.starting_position);
+    for mut transform in camera {
+        transform.translation.x = focus.x;
+        transform.translation.y = -focus.y;
+    }
+}
+
+fn enter_editor(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tiles: Res<Assets<Tiles>>,
+    tilesets: Res<Assets<TileSet>>,
+    overrides: Res<LevelOverrides>,
+    mut editor: ResMut<Editor>,
+    mut manager: Query<&mut LevelManager>,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+    level_entities: Query<Entity, With<LoadedLevel>>,
+) {
+    info!("Entering the level editor");
+    for entity in &level_entities {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Ok(mut manager) = manager.get_single_mut() {
+        if manager.current.is_some() {
+            manager.current = None;
+        }
+    }
+    if editor.playtesting {
+        // keep the unsaved edits from before the playtest
+        editor.playtesting = false;
+        editor.tiles_changed = true;
+        editor.objects_changed = true;
+    } else {
+        load_editor_level(&mut editor, &overrides, &tiles, &tilesets, &asset_server);
+        focus_camera(&editor, &mut camera);
+    }
+
+    let font = asset_server.load("fonts/NotoSans-Regular.ttf");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    left: Val::Px(8.0),
+                    column_gap: Val::Px(8.0),
+                    align_items: AlignItems::Start,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+                ..default()
+            },
+            EditorUi,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(32.0),
+                        height: Val::Px(32.0),
+                        margin: UiRect::all(Val::Px(4.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                BrushPreview,
+            ));
+            p.spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 18.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    TextSection::new(
+                        format!("\n{}", HELP),
+                        TextStyle {
+                            font,
+                            font_size: 14.0,
+                            color: Color::GRAY,
+                        },
+                    ),
+                ]),
+                EditorStatus,
+            ));
+        });
+}
+
+fn exit_editor(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<EditorTile>, With<EditorObject>, With<EditorUi>)>>,
+) {
+    info!("Leaving the level editor");
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn switch_tools(
+    keys: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut tiles: ResMut<Assets<Tiles>>,
+    tilesets: Res<Assets<TileSet>>,
+    mut overrides: ResMut<LevelOverrides>,
+    mut editor: ResMut<Editor>,
+    mut state: ResMut<NextState<GameState>>,
+    mut manager: Query<&mut LevelManager>,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+) {
+    for (key, tool) in [
+        (KeyCode::Key1, Tool::Tiles),
+        (KeyCode::Key2, Tool::Objects),
+        (KeyCode::Key3, Tool::Colliders),
+    ] {
+        if keys.just_pressed(key) && editor.tool != tool {
+            editor.tool = tool;
+            editor.selection = None;
+            editor.collider_start = None;
+        }
+    }
+    if keys.just_pressed(KeyCode::Tab) && !editor.layers.is_empty() {
+        editor.layer = (editor.layer + 1) % editor.layers.len();
+    }
+
+    let levels: Vec<ManagedLevel> = ManagedLevel::levels().collect();
+    let current = levels
+        .iter()
+        .position(|level| *level == editor.level)
+        .unwrap_or(0);
+    let switch_to = if keys.just_pressed(KeyCode::PageDown) {
+        Some((current + 1) % levels.len())
+    } else if keys.just_pressed(KeyCode::PageUp) {
+        Some((current + levels.len() - 1) % levels.len())
+    } else {
+        None
+    };
+    if let Some(index) = switch_to {
+        apply_edits(&editor, &mut overrides, &mut tiles, &asset_server);
+        editor.level = levels[index];
+        load_editor_level(&mut editor, &overrides, &tiles, &tilesets, &asset_server);
+        focus_camera(&editor, &mut camera);
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        apply_edits(&editor, &mut overrides, &mut tiles, &asset_server);
+        editor.data = None;
+        if let Ok(mut manager) = manager.get_single_mut() {
+            manager.next = Some(ManagedLevel::Level1);
+        }
+        state.set(GameState::Menu);
+    }
+}
+
+fn move_editor_camera(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let mut direction = Vec2::ZERO;
+    for (key, offset) in [
+        (KeyCode::W, Vec2::Y),
+        (KeyCode::A, Vec2::NEG_X),
+        (KeyCode::S, Vec2::NEG_Y),
+        (KeyCode::D, Vec2::X),
+    ] {
+        if keys.pressed(key) {
+            direction += offset;
+        }
+    }
+    for mut transform in &mut camera {
+        let step = direction * PAN_SPEED * transform.scale.x * time.delta_seconds();
+        transform.translation += step.extend(0.0);
+        if keys.just_pressed(KeyCode::Z) {
+            transform.scale /= ZOOM_STEP;
+        }
+        if keys.just_pressed(KeyCode::X) {
+            transform.scale *= ZOOM_STEP;
+        }
+    }
+}
+
+fn edit_selection(
+    keys: Res<Input<KeyCode>>,
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut editor: ResMut<Editor>,
+) {
+    if !keys.any_just_pressed([
+        KeyCode::Q,
+        KeyCode::E,
+        KeyCode::B,
+        KeyCode::G,
+        KeyCode::P,
+        KeyCode::F,
+        KeyCode::C,
+        KeyCode::N,
+        KeyCode::Delete,
+        KeyCode::Back,
+    ]) {
+        return;
+    }
+    let previous = keys.just_pressed(KeyCode::Q);
+    let next = keys.just_pressed(KeyCode::E);
+    if editor.tool == Tool::Tiles && !editor.palette.is_empty() {
+        let count = editor.palette.len();
+        if previous {
+            editor.brush = (editor.brush + count - 1) % count;
+        }
+        if next {
+            editor.brush = (editor.brush + 1) % count;
+        }
+    }
+
+    let cursor = cursor_position(&windows, &camera).map(|cursor| snap(cursor, OBJECT_SNAP));
+    let tool = editor.tool;
+    let mut selection = editor.selection;
+    let Some(data) = &mut editor.data else {
+        return;
+    };
+    let mut changed = false;
+    if tool == Tool::Objects {
+        if let Some(cursor) = cursor {
+            if keys.just_pressed(KeyCode::B) {
+                data.buttons.push(ButtonData {
+                    index: 0,
+                    kind: SwitchKind::Momentary,
+                    color: COLORS[0],
+                    position: cursor,
+                });
+                selection = Some(Selection::Button(data.buttons.len() - 1));
+                changed = true;
+            }
+            if keys.just_pressed(KeyCode::G) {
+                data.bridges.push(BridgeData {
+                    index: 0,
+                    negated: false,
+                    color: COLORS[0],
+                    position: cursor,
+                });
+                selection = Some(Selection::Bridge(data.bridges.len() - 1));
+                changed = true;
+            }
+            // every character appears once, new ones still have to be discovered
+            let missing = Character::ALL.iter().find(|character| {
+                !data
+                    .characters
+                    .iter()
+                    .any(|character_data| character_data.character == **character)
+            });
+            if let (true, Some(character)) = (keys.just_pressed(KeyCode::P), missing) {
+                data.characters.push(CharacterData {
+                    character: character.clone(),
+                    starting_position: cursor,
+                    is_discovered: false,
+                });
+                selection = Some(Selection::Character(data.characters.len() - 1));
+                changed = true;
+            }
+            if keys.just_pressed(KeyCode::F) {
+                // the first character of the level without a flag of its own
+                let owner = data
+                    .characters
+                    .iter()
+                    .map(|character_data| &character_data.character)
+                    .find(|character| {
+                        !data
+                            .character_flags
+                            .iter()
+                            .any(|flag| flag.character == **character)
+                    })
+                    .unwrap_or(&data.starting_character);
+                data.character_flags.push(CharacterFlagData {
+                    character: owner.clone(),
+                    position: cursor,
+                });
+                selection = Some(Selection::CharacterFlag(data.character_flags.len() - 1));
+                changed = true;
+            }
+        }
+    }
+
+    let step = match (previous, next) {
+        (true, false) => Some(-1),
+        (false, true) => Some(1),
+        _ => None,
+    };
+    let remove = keys.any_just_pressed([KeyCode::Delete, KeyCode::Back]);
+    match selection {
+        Some(Selection::Button(i)) => {
+            let button = &mut data.buttons[i];
+            if let Some(step) = step {
+                button.index = button.index.saturating_add_signed(step);
+            }
+            if keys.just_pressed(KeyCode::C) {
+                button.color = next_color(button.color);
+            }
+            if remove {
+                data.buttons.remove(i);
+                selection = None;
+            }
+            changed |= step.is_some() || keys.just_pressed(KeyCode::C) || remove;
+        }
+        Some(Selection::Bridge(i)) => {
+            let bridge = &mut data.bridges[i];
+            if let Some(step) = step {
+                bridge.index = bridge.index.saturating_add_signed(step);
+            }
+            if keys.just_pressed(KeyCode::C) {
+                bridge.color = next_color(bridge.color);
+            }
+            if keys.just_pressed(KeyCode::N) {
+                bridge.negated = !bridge.negated;
+            }
+            if remove {
+                data.bridges.remove(i);
+                selection = None;
+            }
+            changed |= step.is_some() || keys.any_just_pressed([KeyCode::C, KeyCode::N]) || remove;
+        }
+        Some(Selection::Collider(i)) => {
+            let collider = &mut data.map_colliders[i];
+            if let Some(step) = step {
+                // no whitelist comes before the characters
+                let current = Character::ALL
+                    .iter()
+                    .position(|character| Some(character) == collider.whitelisted.as_ref())
+                    .map_or(0, |index| index + 1);
+                let count = Character::ALL.len() as isize + 1;
+                let index = (current as isize + step).rem_euclid(count) as usize;
+                collider.whitelisted = index
+                    .checked_sub(1)
+                    .map(|index| Character::ALL[index].clone());
+            }
+            if remove {
+                data.map_colliders.remove(i);
+                selection = None;
+            }
+            changed |= step.is_some() || remove;
+        }
+        Some(Selection::CharacterFlag(i)) => {
+            let flag = &mut data.character_flags[i];
+            if let Some(step) = step {
+                let current = Character::ALL
+                    .iter()
+                    .position(|character| *character == flag.character)
+                    .unwrap_or(0);
+                let count = Character::ALL.len() as isize;
+                let index = (current as isize + step).rem_euclid(count) as usize;
+                flag.character = Character::ALL[index].clone();
+            }
+            if remove {
+                data.character_flags.remove(i);
+                selection = None;
+            }
+            changed |= step.is_some() || remove;
+        }
+        // the level needs the character it starts with
+        Some(Selection::Character(i))
+            if remove && data.characters[i].character != data.starting_character =>
+        {
+            data.characters.remove(i);
+            selection = None;
+            changed = true;
+        }
+        _ => (),
+    }
+    editor.selection = selection;
+    if changed {
+        editor.objects_changed = true;
+        editor.unsaved = true;
+    }
+}
+
+fn paint_tiles(
+    mut commands: Commands,
+    mouse: Res<Input<MouseButton>>,
+    asset_server: Res<AssetServer>,
+    tilemap_atlas: Res<TilemapAtlas>,
+    atlasses: Res<Assets<TextureAtlas>>,
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    editor_tiles: Query<(Entity, &EditorTile)>,
+    mut editor: ResMut<Editor>,
+) {
+    if editor.tool != Tool::Tiles {
+        return;
+    }
+    let tile = if mouse.pressed(MouseButton::Left) {
+        editor.brush_tile().map(|(tile_type, _)| *tile_type)
+    } else if mouse.pressed(MouseButton::Right) {
+        None
+    } else {
+        return;
+    };
+    let Some(cursor) = cursor_position(&windows, &camera) else {
+        return;
+    };
+    let (x, y) = (
+        (cursor.x / TILE_SIZE).round(),
+        (cursor.y / TILE_SIZE).round(),
+    );
+    let layer = editor.layer;
+    let Some(tiles) = editor.layers.get_mut(layer) else {
+        return;
+    };
+    if x < 0.0 || y < 0.0 || x as usize >= tiles.width() || y as usize >= tiles.height() {
+        return;
+    }
+    let (x, y) = (x as usize, y as usize);
+    if tiles.get(x, y) == tile {
+        return;
+    }
+    tiles.set(x, y, tile);
+    editor.unsaved = true;
+    for (entity, editor_tile) in &editor_tiles {
+        if editor_tile.layer == layer && editor_tile.x == x && editor_tile.y == y {
+            commands.entity(entity).despawn();
+        }
+    }
+    if let Some(tile) = tile {
+        spawn_tile(
+            &mut commands,
+            &editor,
+            &asset_server,
+            &tilemap_atlas,
+            &atlasses,
+            EditorTile { layer, x, y },
+            tile,
+        );
+    }
+}
+
+fn edit_objects(
+    mouse: Res<Input<MouseButton>>,
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut editor: ResMut<Editor>,
+) {
+    if editor.tool != Tool::Objects {
+        return;
+    }
+    if mouse.just_released(MouseButton::Left) {
+        editor.dragging = false;
+    }
+    let Some(cursor) = cursor_position(&windows, &camera) else {
+        return;
+    };
+    let Some(data) = &editor.data else {
+        return;
+    };
+    if mouse.just_pressed(MouseButton::Left) {
+        let objects = std::iter::once(Selection::Flag)
+            .chain((0..data.character_flags.len()).map(Selection::CharacterFlag))
+            .chain((0..data.characters.len()).map(Selection::Character))
+            .chain((0..data.buttons.len()).map(Selection::Button))
+            .chain((0..data.bridges.len()).map(Selection::Bridge));
+        let selection = objects
+            .filter_map(|object| {
+                let distance = object.position(data)?.distance(cursor);
+                (distance < PICK_RADIUS).then_some((object, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(object, _)| object);
+        editor.selection = selection;
+        editor.dragging = selection.is_some();
+        editor.objects_changed = true;
+    } else if editor.dragging && mouse.pressed(MouseButton::Left) {
+        let position = snap(cursor, OBJECT_SNAP);
+        let Some(selection) = editor.selection else {
+            return;
+        };
+        if selection.position(data) == Some(position) {
+            return;
+        }
+        if let Some(data) = &mut editor.data {
+            selection.set_position(data, position);
+        }
+        editor.objects_changed = true;
+        editor.unsaved = true;
+    }
+}
+
+fn edit_colliders(
+    mouse: Res<Input<MouseButton>>,
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut editor: ResMut<Editor>,
+) {
+    if editor.tool != Tool::Colliders {
+        return;
+    }
+    let Some(cursor) = cursor_position(&windows, &camera) else {
+        return;
+    };
+    let snapped = snap(cursor, COLLIDER_SNAP);
+    if mouse.just_pressed(MouseButton::Left) {
+        let Some(data) = &editor.data else {
+            return;
+        };
+        // the smallest collider wins so nested colliders stay reachable
+        let hit = data
+            .map_colliders
+            .iter()
+            .enumerate()
+            .filter(|(_, collider)| collider_bounds(collider).contains(cursor))
+            .min_by(|(_, a), (_, b)| (a.size.x * a.size.y).total_cmp(&(b.size.x * b.size.y)))
+            .map(|(i, _)| Selection::Collider(i));
+        editor.selection = hit;
+        editor.collider_start = if hit.is_none() { Some(snapped) } else { None };
+    }
+    if mouse.just_released(MouseButton::Left) {
+        let Some(start) = editor.collider_start.take() else {
+            return;
+        };
+        let bounds = Rect::from_corners(start, snapped);
+        if bounds.width() < COLLIDER_SNAP || bounds.height() < COLLIDER_SNAP {
+            return;
+        }
+        if let Some(data) = &mut editor.data {
+            data.map_colliders.push(SolidColliderData {
+                corner_position: bounds.min + TILE_SIZE / 2.0,
+                size: bounds.size(),
+                whitelisted: None,
+            });
+            editor.selection = Some(Selection::Collider(data.map_colliders.len() - 1));
+        }
+        editor.unsaved = true;
+    }
+}
+
+fn playtest(
+    keys: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut tiles: ResMut<Assets<Tiles>>,
+    mut overrides: ResMut<LevelOverrides>,
+    mut editor: ResMut<Editor>,
+    mut state: ResMut<NextState<GameState>>,
+    mut manager: Query<&mut LevelManager>,
+) {
+    if !keys.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let Ok(mut manager) = manager.get_single_mut() else {
+        return;
+    };
+    info!("Playtesting {:?}", editor.level);
+    apply_edits(&editor, &mut overrides, &mut tiles, &asset_server);
+    manager.next = Some(editor.level);
+    editor.playtesting = true;
+    state.set(GameState::InGame);
+}
+
+fn return_to_editor(
+    keys: Res<Input<KeyCode>>,
+    editor: Res<Editor>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if editor.playtesting && keys.just_pressed(KeyCode::F5) {
+        state.set(GameState::Editor);
+    }
+}
+
+fn save_level(
+    keys: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut tiles: ResMut<Assets<Tiles>>,
+    mut overrides: ResMut<LevelOverrides>,
+    mut editor: ResMut<Editor>,
+) {
+    if !(keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && keys.just_pressed(KeyCode::S))
+    {
+        return;
+    }
+    let Some(data) = &editor.data else {
+        return;
+    };
+    let saved = data
+        .tilemap_layers
+        .iter()
+        .zip(&editor.layers)
+        .try_for_each(|(path, layer)| {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("assets")
+                .join(path);
+            std::fs::write(&path, layer.to_csv())
+                .map_err(|err| format!("{}: {}", path.display(), err))
+        })
+        .and_then(|_| source::write_level_source(editor.level as usize, data));
+    if let Err(err) = saved {
+        error!("Could not save {:?}: {}", editor.level, err);
+        editor.error = Some(format!("Save failed: {}", err));
+        return;
+    }
+    info!("Saved {:?}", editor.level);
+    apply_edits(&editor, &mut overrides, &mut tiles, &asset_server);
+    editor.unsaved = false;
+    editor.error = None;
+}
+
+fn spawn_tile(
+    commands: &mut Commands,
+    editor: &Editor,
+    asset_server: &AssetServer,
+    tilemap_atlas: &TilemapAtlas,
+    atlasses: &Assets<TextureAtlas>,
+    editor_tile: EditorTile,
+    tile: usize,
+) {
+    let (Some(name), Some(atlas_handle)) = (editor.texture_name(tile), &tilemap_atlas.tilemap)
+    else {
+        return;
+    };
+    let Some(index) = atlasses
+        .get(atlas_handle)
+        .and_then(|atlas| atlas.get_texture_index(&asset_server.get_handle(name)))
+    else {
+        return;
+    };
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: atlas_handle.clone(),
+            sprite: TextureAtlasSprite::new(index),
+            transform: Transform::from_xyz(
+                editor_tile.x as f32 * TILE_SIZE,
+                -(editor_tile.y as f32) * TILE_SIZE,
+                editor_tile.layer as f32,
+            )
+            .with_scale(Vec3::new(1.02, 1.02, 1.0)),
+            ..default()
+        },
+        editor_tile,
+    ));
+}
+
+fn spawn_editor_tiles(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tilemap_atlas: Res<TilemapAtlas>,
+    atlasses: Res<Assets<TextureAtlas>>,
+    editor_tiles: Query<Entity, With<EditorTile>>,
+    mut editor: ResMut<Editor>,
+) {
+    if !editor.tiles_changed {
+        return;
+    }
+    editor.tiles_changed = false;
+    for entity in &editor_tiles {
+        commands.entity(entity).despawn();
+    }
+    for (layer, tiles) in editor.layers.iter().enumerate() {
+        for y in 0..tiles.height() {
+            for x in 0..tiles.width() {
+                if let Some(tile) = tiles.get(x, y) {
+                    spawn_tile(
+                        &mut commands,
+                        &editor,
+                        &asset_server,
+                        &tilemap_atlas,
+                        &atlasses,
+                        EditorTile { layer, x, y },
+                        tile,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn spawn_object(
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    color: Color,
+    position: Vec2,
+    z: f32,
+    label: Option<(String, &TextStyle)>,
+) {
+    let position = to_world(position);
+    commands
+        .spawn((
+            SpriteBundle {
+                texture,
+                sprite: Sprite { color, ..default() },
+                transform: Transform::from_xyz(position.x, position.y, z),
+                ..default()
+            },
+            EditorObject,
+        ))
+        .with_children(|p| {
+            if let Some((label, style)) = label {
+                p.spawn(Text2dBundle {
+                    text: Text::from_section(label, style.clone()),
+                    transform: Transform::from_xyz(0.0, TILE_SIZE / 2.0, 1.0),
+                    ..default()
+                });
+            }
+        });
+}
+
+fn spawn_editor_objects(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    objects: Query<Entity, With<EditorObject>>,
+    mut editor: ResMut<Editor>,
+) {
+    if !editor.objects_changed {
+        return;
+    }
+    editor.objects_changed = false;
+    for entity in &objects {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(data) = &editor.data else {
+        return;
+    };
+    let label_style = TextStyle {
+        font: asset_server.load("fonts/NotoSans-Regular.ttf"),
+        font_size: LABEL_SIZE,
+        color: Color::WHITE,
+    };
+    let flag = asset_server.load("tilemap/flag.png");
+    spawn_object(
+        &mut commands,
+        flag.clone(),
+        Color::WHITE,
+        data.flag_position,
+        5.0,
+        None,
+    );
+    for flag_data in &data.character_flags {
+        spawn_object(
+            &mut commands,
+            flag.clone(),
+            flag_data.character.color(),
+            flag_data.position,
+            5.0,
+            None,
+        );
+    }
+    for button in &data.buttons {
+        spawn_object(
+            &mut commands,
+            asset_server.load(button.kind.texture(false)),
+            button.color,
+            button.position,
+            5.0,
+            Some((button.index.to_string(), &label_style)),
+        );
+    }
+    for bridge in &data.bridges {
+        let label = if bridge.negated {
+            format!("!{}", bridge.index)
+        } else {
+            bridge.index.to_string()
+        };
+        spawn_object(
+            &mut commands,
+            asset_server.load(ActuatorKind::BridgeGate.texture()),
+            bridge.color,
+            bridge.position,
+            5.0,
+            Some((label, &label_style)),
+        );
+    }
+    for character in &data.characters {
+        // undiscovered characters are see-through
+        let alpha = if character.is_discovered { 1.0 } else { 0.5 };
+        spawn_object(
+            &mut commands,
+            character.character.face_texture(&asset_server),
+            Color::rgba(1.0, 1.0, 1.0, alpha),
+            character.starting_position,
+            10.0,
+            None,
+        );
+    }
+}
+
+fn draw_editor_gizmos(
+    mut gizmos: Gizmos,
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    editor: Res<Editor>,
+) {
+    let Some(data) = &editor.data else {
+        return;
+    };
+    if let Some(tiles) = editor.layers.first() {
+        let size = Vec2::new(tiles.width() as f32, tiles.height() as f32) * TILE_SIZE;
+        let center = (size - TILE_SIZE) / 2.0;
+        gizmos.rect_2d(to_world(center), 0.0, size, Color::GRAY);
+    }
+    for (i, collider) in data.map_colliders.iter().enumerate() {
+        let bounds = collider_bounds(collider);
+        let color = if editor.selection == Some(Selection::Collider(i)) {
+            Color::YELLOW
+        } else {
+            collider
+                .whitelisted
+                .as_ref()
+                .map_or(Color::RED, |character| character.color())
+        };
+        gizmos.rect_2d(to_world(bounds.center()), 0.0, bounds.size(), color);
+    }
+    if let Some(position) = editor
+        .selection
+        .filter(|selection| !matches!(selection, Selection::Collider(_)))
+        .and_then(|selection| selection.position(data))
+    {
+        gizmos.circle_2d(to_world(position), PICK_RADIUS, Color::YELLOW);
+    }
+
+    let Some(cursor) = cursor_position(&windows, &camera) else {
+        return;
+    };
+    match editor.tool {
+        Tool::Tiles => {
+            let tile = snap(cursor, TILE_SIZE);
+            gizmos.rect_2d(to_world(tile), 0.0, Vec2::splat(TILE_SIZE), Color::WHITE);
+        }
+        Tool::Objects => {
+            gizmos.circle_2d(to_world(snap(cursor, OBJECT_SNAP)), 2.0, Color::WHITE);
+        }
+        Tool::Colliders => {
+            let snapped = snap(cursor, COLLIDER_SNAP);
+            if let Some(start) = editor.collider_start {
+                let bounds = Rect::from_corners(start, snapped);
+                gizmos.rect_2d(to_world(bounds.center()), 0.0, bounds.size(), Color::WHITE);
+            } else {
+                gizmos.circle_2d(to_world(snapped), 1.0, Color::WHITE);
+            }
+        }
+    }
+}
+
+fn update_editor_status(
+    asset_server: Res<AssetServer>,
+    tilesets: Res<Assets<TileSet>>,
+    editor: Res<Editor>,
+    mut status: Query<&mut Text, With<EditorStatus>>,
+    mut preview: Query<&mut UiImage, With<BrushPreview>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    let Some(data) = &editor.data else {
+        if let Some(err) = &editor.error {
+            for mut text in &mut status {
+                text.sections[0].value = format!("{:?}  {}", editor.level, err);
+            }
+        }
+        return;
+    };
+    let issues = tilesets
+        .get(&asset_server.get_handle(data.tileset.as_str()))
+        .zip(editor.layers.first())
+        .and_then(|(tile_set, ground)| Tilemap::new(tile_set, ground))
+        .map_or(0, |ground| validate_level(data, &ground).len());
+    let layer = data
+        .tilemap_layers
+        .get(editor.layer)
+        .and_then(|path| Path::new(path).file_stem())
+        .map_or(String::new(), |name| name.to_string_lossy().to_string());
+    let brush = editor.brush_tile().map_or("-", |(_, name)| name.as_str());
+    let mut line = format!(
+        "{:?}{}  {:?}  layer {} ({})  brush {}",
+        editor.level,
+        if editor.unsaved { "*" } else { "" },
+        editor.tool,
+        editor.layer,
+        layer,
+        brush
+    );
+    match editor.selection {
+        Some(Selection::Button(i)) => {
+            let button = &data.buttons[i];
+            line += &format!("  button index {} {:?}", button.index, button.kind);
+        }
+        Some(Selection::Bridge(i)) => {
+            let bridge = &data.bridges[i];
+            line += &format!(
+                "  bridge index {}{}",
+                bridge.index,
+                if bridge.negated { " negated" } else { "" }
+            );
+        }
+        Some(Selection::Collider(i)) => {
+            let whitelisted = &data.map_colliders[i].whitelisted;
+            line += &format!("  collider whitelist {:?}", whitelisted);
+        }
+        Some(selection) => line += &format!("  {:?}", selection),
+        None => (),
+    }
+    if issues > 0 {
+        line += &format!("  {} issues", issues);
+    }
+    if let Some(err) = &editor.error {
+        line += &format!("\n{}", err);
+    }
+    for mut text in &mut status {
+        text.sections[0].value = line.clone();
+    }
+    for mut image in &mut preview {
+        if let Some((_, name)) = editor.brush_tile() {
+            image.texture = asset_server.load(name.as_str());
+        }
+    }
+}
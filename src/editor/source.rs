@@ -0,0 +1,571 @@
+use bevy::prelude::*;
+
+use crate::gamelogic::{
+    budget::MoveBudget,
+    character::Character,
+    goal::WinCondition,
+    inventory::Item,
+    level::{ActuatorKind, SwitchKind},
+    level_mgr::{HintTarget, LevelData, TILE_SIZE},
+    signal::Gate,
+};
+
+// the game is run with `cargo run`, so the sources are next to the manifest
+const LEVEL_MGR_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/gamelogic/level_mgr.rs");
+
+fn number(value: f32) -> String {
+    format!("{:?}", (value * 100.0).round() / 100.0)
+}
+
+fn position(position: Vec2) -> String {
+    format!(
+        "Vec2::new({} * TILE_SIZE, {} * TILE_SIZE)",
+        number(position.x / TILE_SIZE),
+        number(position.y / TILE_SIZE)
+    )
+}
+
+fn color(color: Color) -> String {
+    let [r, g, b, a] = color.as_rgba_f32();
+    if a == 1.0 {
+        format!("Color::rgb({}, {}, {})", number(r), number(g), number(b))
+    } else {
+        format!(
+            "Color::rgba({}, {}, {}, {})",
+            number(r),
+            number(g),
+            number(b),
+            number(a)
+        )
+    }
+}
+
+fn character(character: &Character) -> String {
+    format!("Character::{:?}", character)
+}
+
+fn string(value: &str) -> String {
+    format!("{:?}.to_string()", value)
+}
+
+fn strings(values: &[String]) -> String {
+    let values: Vec<String> = values.iter().map(|value| string(value)).collect();
+    format!("vec![{}]", values.join(", "))
+}
+
+fn option(value: Option<String>) -> String {
+    value.map_or("None".to_string(), |value| format!("Some({})", value))
+}
+
+fn switch_kind(kind: &SwitchKind) -> String {
+    match kind {
+        SwitchKind::Momentary => "SwitchKind::Momentary".to_string(),
+        SwitchKind::Lever => "SwitchKind::Lever".to_string(),
+        SwitchKind::Timed { seconds } => {
+            format!("SwitchKind::Timed {{ seconds: {} }}", number(*seconds))
+        }
+        SwitchKind::Character(owner) => format!("SwitchKind::Character({})", character(owner)),
+        SwitchKind::Weight { bodies } => format!("SwitchKind::Weight {{ bodies: {} }}", bodies),
+    }
+}
+
+fn actuator_kind(kind: &ActuatorKind) -> String {
+    match kind {
+        ActuatorKind::Platform { offset } => {
+            format!("ActuatorKind::Platform {{ offset: {} }}", position(*offset))
+        }
+        kind => format!("ActuatorKind::{:?}", kind),
+    }
+}
+
+fn gate(gate: &Gate) -> String {
+    match gate {
+        Gate::Delay {
+            rise_seconds,
+            fall_seconds,
+        } => format!(
+            "Gate::Delay {{ rise_seconds: {}, fall_seconds: {} }}",
+            number(*rise_seconds),
+            number(*fall_seconds)
+        ),
+        gate => format!("Gate::{:?}", gate),
+    }
+}
+
+fn win_condition(condition: &WinCondition) -> String {
+    match condition {
+        WinCondition::CharactersAtFlag(characters) => {
+            let characters: Vec<String> = characters.iter().map(character).collect();
+            format!(
+                "WinCondition::CharactersAtFlag(vec![{}])",
+                characters.join(", ")
+            )
+        }
+        condition => format!("WinCondition::{:?}", condition),
+    }
+}
+
+fn item(item: &Item) -> String {
+    match item {
+        Item::Key(key) => format!("Item::Key(KeyColor::{:?})", key),
+    }
+}
+
+fn hint_target(target: &HintTarget) -> String {
+    match target {
+        HintTarget::Character(owner) => format!("HintTarget::Character({})", character(owner)),
+        target => format!("HintTarget::{:?}", target),
+    }
+}
+
+fn budget(budget: &MoveBudget) -> String {
+    match budget {
+        MoveBudget::Distance(distance) => format!("MoveBudget::Distance({})", number(*distance)),
+        budget => format!("MoveBudget::{:?}", budget),
+    }
+}
+
+fn structure(name: &str, fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(field, value)| format!("{}: {},", field, value))
+        .collect();
+    format!("{} {{\n{}\n}}", name, fields.join("\n"))
+}
+
+fn list(items: Vec<String>) -> String {
+    if items.is_empty() {
+        "vec![]".to_string()
+    } else {
+        format!("vec![\n{},\n]", items.join(",\n"))
+    }
+}
+
+pub fn level_source(data: &LevelData) -> String {
+    let fields = [
+        (
+            "next_level",
+            option(
+                data.next_level
+                    .map(|level| format!("ManagedLevel::{:?}", level)),
+            ),
+        ),
+        ("par_seconds", number(data.par_seconds)),
+        ("max_switches", data.max_switches.to_string()),
+        ("budget", option(data.budget.as_ref().map(budget))),
+        ("flag_position", position(data.flag_position)),
+        ("tileset", string(&data.tileset)),
+        (
+            "tilemap_layers",
+            list(
+                data.tilemap_layers
+                    .iter()
+                    .map(|layer| string(layer))
+                    .collect(),
+            ),
+        ),
+        (
+            "win_conditions",
+            list(data.win_conditions.iter().map(win_condition).collect()),
+        ),
+        (
+            "character_flags",
+            list(
+                data.character_flags
+                    .iter()
+                    .map(|flag| {
+                        structure(
+                            "CharacterFlagData",
+                            &[
+                                ("character", character(&flag.character)),
+                                ("position", position(flag.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "collectibles",
+            list(
+                data.collectibles
+                    .iter()
+                    .map(|collectible| {
+                        structure(
+                            "CollectibleData",
+                            &[("position", position(collectible.position))],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        ("starting_character", character(&data.starting_character)),
+        (
+            "characters",
+            list(
+                data.characters
+                    .iter()
+                    .map(|character_data| {
+                        structure(
+                            "CharacterData",
+                            &[
+                                ("character", character(&character_data.character)),
+                                (
+                                    "starting_position",
+                                    position(character_data.starting_position),
+                                ),
+                                ("is_discovered", character_data.is_discovered.to_string()),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "buttons",
+            list(
+                data.buttons
+                    .iter()
+                    .map(|button| {
+                        structure(
+                            "ButtonData",
+                            &[
+                                ("index", button.index.to_string()),
+                                ("kind", switch_kind(&button.kind)),
+                                ("color", color(button.color)),
+                                ("position", position(button.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "map_colliders",
+            list(
+                data.map_colliders
+                    .iter()
+                    .map(|collider| {
+                        structure(
+                            "SolidColliderData",
+                            &[
+                                (
+                                    "whitelisted",
+                                    option(collider.whitelisted.as_ref().map(character)),
+                                ),
+                                ("corner_position", position(collider.corner_position)),
+                                ("size", position(collider.size)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "bridges",
+            list(
+                data.bridges
+                    .iter()
+                    .map(|bridge| {
+                        structure(
+                            "BridgeData",
+                            &[
+                                ("index", bridge.index.to_string()),
+                                ("negated", bridge.negated.to_string()),
+                                ("color", color(bridge.color)),
+                                ("position", position(bridge.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "actuators",
+            list(
+                data.actuators
+                    .iter()
+                    .map(|actuator| {
+                        structure(
+                            "ActuatorData",
+                            &[
+                                ("kind", actuator_kind(&actuator.kind)),
+                                ("channel", string(&actuator.channel)),
+                                ("negated", actuator.negated.to_string()),
+                                ("color", color(actuator.color)),
+                                ("position", position(actuator.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "teleporters",
+            list(
+                data.teleporters
+                    .iter()
+                    .map(|teleporter| {
+                        structure(
+                            "TeleporterData",
+                            &[
+                                ("pair", teleporter.pair.to_string()),
+                                ("channel", option(teleporter.channel.as_deref().map(string))),
+                                ("color", color(teleporter.color)),
+                                ("position", position(teleporter.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "hazards",
+            list(
+                data.hazards
+                    .iter()
+                    .map(|hazard| {
+                        structure(
+                            "HazardData",
+                            &[
+                                ("kind", format!("HazardKind::{:?}", hazard.kind)),
+                                ("corner_position", position(hazard.corner_position)),
+                                ("size", position(hazard.size)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "checkpoints",
+            list(
+                data.checkpoints
+                    .iter()
+                    .map(|checkpoint| {
+                        structure(
+                            "CheckpointData",
+                            &[("position", position(checkpoint.position))],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "items",
+            list(
+                data.items
+                    .iter()
+                    .map(|item| {
+                        structure(
+                            "ItemData",
+                            &[
+                                ("item", self::item(&item.item)),
+                                ("position", position(item.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "locked_gates",
+            list(
+                data.locked_gates
+                    .iter()
+                    .map(|gate| {
+                        structure(
+                            "LockedGateData",
+                            &[
+                                ("key", format!("KeyColor::{:?}", gate.key)),
+                                ("consumes_key", gate.consumes_key.to_string()),
+                                ("position", position(gate.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "crates",
+            list(
+                data.crates
+                    .iter()
+                    .map(|crate_data| {
+                        structure(
+                            "CrateData",
+                            &[
+                                ("mass", number(crate_data.mass)),
+                                ("position", position(crate_data.position)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "pits",
+            list(
+                data.pits
+                    .iter()
+                    .map(|pit| structure("PitData", &[("position", position(pit.position))]))
+                    .collect(),
+            ),
+        ),
+        (
+            "character_collisions",
+            data.character_collisions.to_string(),
+        ),
+        (
+            "signals",
+            list(
+                data.signals
+                    .iter()
+                    .map(|node| {
+                        structure(
+                            "SignalNodeData",
+                            &[
+                                ("gate", gate(&node.gate)),
+                                ("inputs", strings(&node.inputs)),
+                                ("outputs", strings(&node.outputs)),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "hints",
+            list(
+                data.hints
+                    .iter()
+                    .map(|hint| {
+                        structure(
+                            "HintData",
+                            &[
+                                ("text", string(&hint.text)),
+                                ("target", option(hint.target.as_ref().map(hint_target))),
+                            ],
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+    ];
+    structure("LevelData", &fields)
+}
+
+fn indented(source: &str, indent: usize) -> String {
+    let mut depth = indent;
+    let mut lines = Vec::new();
+    for line in source.lines() {
+        if line.starts_with(['}', ']', ')']) {
+            depth -= 1;
+        }
+        lines.push(format!("{}{}", "    ".repeat(depth), line));
+        if line.ends_with(['{', '[', '(']) {
+            depth += 1;
+        }
+    }
+    lines.join("\n")
+}
+
+// change in bracket depth of a line, brackets in string literals don't count
+fn depth_change(line: &str) -> isize {
+    let mut change = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for letter in line.chars() {
+        match letter {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' | '[' | '(' if !in_string => change += 1,
+            '}' | ']' | ')' if !in_string => change -= 1,
+            _ => {}
+        }
+    }
+    change
+}
+
+// first and last line of the literal of the level with the given index
+fn find_literal(lines: &[&str], index: usize) -> Result<(usize, usize), String> {
+    let start = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim() == "LevelData {")
+        .nth(index)
+        .map(|(i, _)| i)
+        .ok_or(format!(
+            "Level {} not found in {}",
+            index + 1,
+            LEVEL_MGR_PATH
+        ))?;
+    let mut depth = 0;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        depth += depth_change(line);
+        if depth == 0 {
+            return Ok((start, i));
+        }
+    }
+    Err(format!(
+        "Level {} is not terminated in {}",
+        index + 1,
+        LEVEL_MGR_PATH
+    ))
+}
+
+// replaces the literal of the level with the given index in `level_mgr.rs`
+pub fn write_level_source(index: usize, data: &LevelData) -> Result<(), String> {
+    let source = std::fs::read_to_string(LEVEL_MGR_PATH)
+        .map_err(|err| format!("{}: {}", LEVEL_MGR_PATH, err))?;
+    let lines: Vec<&str> = source.lines().collect();
+    let (start, end) = find_literal(&lines, index)?;
+    let indent = lines[start].len() - lines[start].trim_start().len();
+    let literal = indented(&format!("{},", level_source(data)), indent / 4);
+    let mut result: Vec<&str> = lines[..start].to_vec();
+    result.extend(literal.lines());
+    result.extend(&lines[end + 1..]);
+    std::fs::write(LEVEL_MGR_PATH, result.join("\n") + "\n")
+        .map_err(|err| format!("{}: {}", LEVEL_MGR_PATH, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamelogic::level_mgr::ManagedLevel;
+
+    // line breaks and trailing commas are up to rustfmt
+    fn normalized(source: &str) -> String {
+        let mut source = source.split_whitespace().collect::<Vec<_>>().join(" ");
+        for (from, to) in [
+            ("[ ", "["),
+            ("( ", "("),
+            (" ]", "]"),
+            (" )", ")"),
+            (" }", "}"),
+            (",]", "]"),
+            (",)", ")"),
+            (",}", "}"),
+        ] {
+            source = source.replace(from, to);
+        }
+        source
+    }
+
+    #[test]
+    fn saving_an_unchanged_level_keeps_its_source() {
+        let source = std::fs::read_to_string(LEVEL_MGR_PATH).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        for (index, level) in ManagedLevel::levels().enumerate() {
+            let (start, end) = find_literal(&lines, index).unwrap();
+            let literal = lines[start..=end].join("\n");
+            assert_eq!(
+                normalized(&literal),
+                normalized(&format!("{},", level_source(level.get_data()))),
+                "the literal of {:?} differs from what the editor writes",
+                level
+            );
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::{ecs::schedule::ExecutorKind, prelude::*};
+
+use crate::{GameState, MainCamera};
+
+pub const TICKS_PER_SECOND: f32 = 60.0;
+
+// everything the player can do during one tick of the simulation
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerActions {
+    pub movement: Vec2,
+    // index into the discovered characters
+    pub switch_to: Option<usize>,
+    pub toggle_camera: bool,
+    pub interact: bool,
+    pub pass_item: bool,
+    pub toggle_follow: bool,
+    pub walk_to: Option<Vec2>,
+}
+
+#[derive(Resource, Default)]
+pub struct Actions {
+    // collected from the keyboard and mouse until the next tick
+    pub pending: PlayerActions,
+    // what the simulation acts on during the current tick
+    pub current: PlayerActions,
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActionSet;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(1.0 / TICKS_PER_SECOND));
+        app.init_resource::<Actions>();
+        // the order of unordered systems must not change between runs for replays to work
+        app.edit_schedule(FixedUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+        app.add_systems(
+            Update,
+            (read_keyboard, read_clicks).run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            begin_tick
+                .in_set(ActionSet)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn read_keyboard(keys: Res<Input<KeyCode>>, mut actions: ResMut<Actions>) {
+    let pending = &mut actions.pending;
+    pending.movement = Vec2::new(
+        if keys.pressed(KeyCode::A) {
+            -1.0
+        } else if keys.pressed(KeyCode::D) {
+            1.0
+        } else {
+            0.0
+        },
+        if keys.pressed(KeyCode::W) {
+            1.0
+        } else if keys.pressed(KeyCode::S) {
+            -1.0
+        } else {
+            0.0
+        },
+    );
+    let number_keys = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    if let Some(number) = number_keys.iter().position(|key| keys.just_pressed(*key)) {
+        pending.switch_to = Some(number);
+    }
+    pending.toggle_camera |= keys.just_pressed(KeyCode::F);
+    pending.interact |= keys.just_pressed(KeyCode::E);
+    pending.pass_item |= keys.just_pressed(KeyCode::Q);
+    pending.toggle_follow |= keys.just_pressed(KeyCode::G);
+}
+
+fn read_clicks(
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut actions: ResMut<Actions>,
+) {
+    let screen_position = if mouse.just_pressed(MouseButton::Left) {
+        windows
+            .get_single()
+            .ok()
+            .and_then(|window| window.cursor_position())
+    } else {
+        touches
+            .iter_just_pressed()
+            .next()
+            .map(|touch| touch.position())
+    };
+    let Some(screen_position) = screen_position else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+    if let Some(target) = camera.viewport_to_world_2d(camera_transform, screen_position) {
+        actions.pending.walk_to = Some(target);
+    }
+}
+
+// held movement carries over to the next tick, everything else only happens once
+pub fn begin_tick(mut actions: ResMut<Actions>) {
+    let movement = actions.pending.movement;
+    actions.current = std::mem::replace(
+        &mut actions.pending,
+        PlayerActions {
+            movement,
+            ..default()
+        },
+    );
+}
@@ -0,0 +1,216 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::GameState;
+
+use super::{
+    character::{Character, DiscoveredCharacters},
+    level_mgr::{LevelManager, LoadedLevel, TILE_SIZE},
+    navigation::{tile_of, Tile},
+    rating::LevelStats,
+};
+
+// anything further within one frame is a teleport or respawn, not walking
+const MAX_STEP: f32 = TILE_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveBudget {
+    Distance(f32),
+    Switches(usize),
+    Tiles(usize),
+}
+
+impl MoveBudget {
+    pub fn limit(&self) -> f32 {
+        match self {
+            MoveBudget::Distance(distance) => *distance,
+            MoveBudget::Switches(switches) => *switches as f32,
+            MoveBudget::Tiles(tiles) => *tiles as f32,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MoveBudget::Distance(_) => "Distance",
+            MoveBudget::Switches(_) => "Switches",
+            MoveBudget::Tiles(_) => "Tiles",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ChallengeMode {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+pub struct BudgetMeter {
+    pub budget: MoveBudget,
+    pub used: f32,
+    pub failed: bool,
+    last_positions: HashMap<Character, Vec2>,
+    visited: HashSet<Tile>,
+}
+
+impl BudgetMeter {
+    pub fn new(budget: MoveBudget) -> BudgetMeter {
+        BudgetMeter {
+            budget,
+            used: 0.0,
+            failed: false,
+            last_positions: HashMap::default(),
+            visited: HashSet::default(),
+        }
+    }
+
+    pub fn remaining_fraction(&self) -> f32 {
+        (1.0 - self.used / self.budget.limit()).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Component)]
+struct LevelFailedScreen;
+
+pub struct BudgetPlugin;
+
+impl Plugin for BudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChallengeMode>();
+        app.add_systems(
+            FixedUpdate,
+            track_budget.run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            (show_level_failed, restart_level)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn track_budget(
+    challenge: Res<ChallengeMode>,
+    player_query: Query<&DiscoveredCharacters>,
+    stats: Query<&LevelStats>,
+    characters: Query<(&Character, &Transform)>,
+    mut meters: Query<&mut BudgetMeter>,
+) {
+    let (Ok(mut meter), Ok(discovered)) = (meters.get_single_mut(), player_query.get_single())
+    else {
+        return;
+    };
+    if !challenge.enabled || meter.failed {
+        return;
+    }
+    let meter = &mut *meter;
+    for (character, transform) in &characters {
+        if !discovered.discovered.contains(character) {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        if let Some(last) = meter.last_positions.insert(character.clone(), position) {
+            let step = last.distance(position);
+            if matches!(meter.budget, MoveBudget::Distance(_)) && step < MAX_STEP {
+                meter.used += step;
+            }
+        }
+        meter.visited.insert(tile_of(position));
+    }
+    match meter.budget {
+        MoveBudget::Distance(_) => {}
+        MoveBudget::Switches(_) => {
+            meter.used = stats
+                .get_single()
+                .map_or(0.0, |stats| stats.switches as f32);
+        }
+        MoveBudget::Tiles(_) => meter.used = meter.visited.len() as f32,
+    }
+    if meter.used > meter.budget.limit() {
+        meter.failed = true;
+        info!(
+            "{} budget of {} exceeded",
+            meter.budget.label(),
+            meter.budget.limit()
+        );
+    }
+}
+
+fn show_level_failed(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    manager: Query<&LevelManager>,
+    meters: Query<&BudgetMeter, Changed<BudgetMeter>>,
+    screens: Query<(), With<LevelFailedScreen>>,
+) {
+    let (Ok(meter), Ok(manager)) = (meters.get_single(), manager.get_single()) else {
+        return;
+    };
+    let Some(level) = manager.current else {
+        return;
+    };
+    if !meter.failed || !screens.is_empty() {
+        return;
+    }
+    commands.spawn(AudioBundle {
+        source: asset_server.load("sounds/switch_character.ogg"),
+        settings: PlaybackSettings {
+            mode: PlaybackMode::Despawn,
+            volume: Volume::new_absolute(1.0),
+            speed: 0.5,
+            paused: false,
+        },
+    });
+    let font: Handle<Font> = asset_server.load("fonts/NotoSans-Regular.ttf");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+                ..default()
+            },
+            LevelFailedScreen,
+            LoadedLevel { level },
+        ))
+        .with_children(|p| {
+            p.spawn(TextBundle::from_section(
+                format!("Out of {}!", meter.budget.label().to_lowercase()),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 48.0,
+                    color: Color::WHITE,
+                },
+            ));
+            p.spawn(TextBundle::from_section(
+                "Press R to restart",
+                TextStyle {
+                    font,
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn restart_level(
+    keys: Res<Input<KeyCode>>,
+    screens: Query<(), With<LevelFailedScreen>>,
+    mut manager: Query<&mut LevelManager>,
+) {
+    if screens.is_empty() || !keys.just_pressed(KeyCode::R) {
+        return;
+    }
+    let mut manager = manager.single_mut();
+    manager.next = manager.current;
+}
@@ -5,7 +5,11 @@ use bevy::{
 
 use crate::{util::Lerp, GameState, MainCamera};
 
-use super::character::{Character, CurrentCharacter, DiscoveredCharacters};
+use super::{
+    actions::{ActionSet, Actions},
+    character::{Character, CurrentCharacter, DiscoveredCharacters},
+    teleport::Teleported,
+};
 
 pub enum CameraMode {
     AllCharacters,
@@ -33,8 +37,10 @@ impl Plugin for CameraControlPlugin {
             current: CameraMode::CurrentCharacter,
         });
         app.add_systems(
-            Update,
-            switch_camera_mode.run_if(in_state(GameState::InGame)),
+            FixedUpdate,
+            switch_camera_mode
+                .after(ActionSet)
+                .run_if(in_state(GameState::InGame)),
         );
         app.add_systems(Update, camera_movement.run_if(in_state(GameState::InGame)));
     }
@@ -52,7 +58,7 @@ impl CurrentCameraMode {
 fn switch_camera_mode(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     discovered: Query<&DiscoveredCharacters>,
     mut camera_mode: ResMut<CurrentCameraMode>,
 ) {
@@ -65,7 +71,7 @@ fn switch_camera_mode(
         false
     };
     // switch camera mode
-    if keys.just_pressed(KeyCode::F) && can_switch {
+    if actions.current.toggle_camera && can_switch {
         commands.spawn(AudioBundle {
             source: asset_server.load("sounds/switch_camera.ogg"),
             settings: PlaybackSettings {
@@ -84,6 +90,7 @@ const CAMERA_PADDING: f32 = 128.0;
 
 fn camera_movement(
     camera_mode: Res<CurrentCameraMode>,
+    mut teleported: EventReader<Teleported>,
     mut param_set: ParamSet<(
         Query<&mut Transform, With<MainCamera>>,
         Query<&Window>,
@@ -96,6 +103,15 @@ fn camera_movement(
     } else {
         return;
     };
+    // jump along with a teleported character instead of sweeping across the map
+    let jump: Vec2 = teleported
+        .iter()
+        .filter(|event| {
+            matches!(camera_mode.current, CameraMode::CurrentCharacter)
+                && event.character == character
+        })
+        .map(|event| event.offset)
+        .sum();
     let (x, y, scale) = match camera_mode.current {
         CameraMode::AllCharacters => {
             let character_transforms: Vec<Transform> = param_set
@@ -143,6 +159,7 @@ fn camera_movement(
         }
     };
     for mut camera_transform in param_set.p0().iter_mut() {
+        camera_transform.translation += jump.extend(0.0);
         camera_transform.translation.x = camera_transform.translation.x.lerp(x, 0.3);
         camera_transform.translation.y = camera_transform.translation.y.lerp(y, 0.3);
         camera_transform.scale.x = camera_transform.scale.x.lerp(scale, 0.3);
@@ -4,36 +4,34 @@ use bevy::{
 };
 
 use crate::{
-    physics::{CollisionBox, Solid},
+    physics::{total_penetration, Collider, CollisionBox, Pushable, Solid},
     GameState,
 };
 
 use super::{
-    level::{GoalFlag, PushButton},
-    level_mgr::LevelManager,
+    actions::{ActionSet, Actions},
+    ground::Movement,
+    level::{Pit, PushButton, SwitchKind},
+    navigation::NavigationPath,
 };
 
 pub struct CharacterPlugin;
 impl Plugin for CharacterPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            switch_characters.run_if(in_state(GameState::InGame)),
+            FixedUpdate,
+            (switch_characters, toggle_levers, player_movement)
+                .after(ActionSet)
+                .run_if(in_state(GameState::InGame)),
         );
         app.add_systems(
-            Update,
-            trigger_meet_character.run_if(in_state(GameState::InGame)),
+            FixedUpdate,
+            (trigger_meet_character, trigger_push_buttons).run_if(in_state(GameState::InGame)),
         );
-        app.add_systems(
-            Update,
-            trigger_push_buttons.run_if(in_state(GameState::InGame)),
-        );
-        app.add_systems(Update, trigger_flag.run_if(in_state(GameState::InGame)));
         app.add_systems(
             Update,
             update_animations.run_if(in_state(GameState::InGame)),
         );
-        app.add_systems(Update, player_movement.run_if(in_state(GameState::InGame)));
     }
 }
 
@@ -56,7 +54,7 @@ fn update_animations(
     }
 }
 
-#[derive(Component, PartialEq, Eq, Debug, Clone)]
+#[derive(Component, PartialEq, Eq, Hash, Debug, Clone)]
 pub enum Character {
     Turtle,
     Rabbit,
@@ -73,6 +71,30 @@ pub struct AnimationFrames {
 pub struct AnimationTimer(pub Timer);
 
 impl Character {
+    pub const ALL: [Character; 4] = [
+        Character::Turtle,
+        Character::Rabbit,
+        Character::Crocodile,
+        Character::Lizard,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Character::Turtle => "Turtle",
+            Character::Rabbit => "Rabbit",
+            Character::Crocodile => "Crocodile",
+            Character::Lizard => "Lizard",
+        }
+    }
+
+    // ignores case, so console input and saved data both work
+    pub fn from_name(name: &str) -> Option<Character> {
+        Character::ALL
+            .iter()
+            .find(|character| character.name().eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
     pub fn color(&self) -> Color {
         match self {
             Character::Turtle => Color::hsl(73.0, 0.7, 0.75),
@@ -131,10 +153,65 @@ impl Character {
         }
     }
 
-    fn speed(&self, time: &Res<Time>) -> f32 {
+    pub fn footstep_sounds(&self) -> &'static [&'static str] {
+        match self {
+            Character::Turtle => &["sounds/turtle_walk.ogg"],
+            Character::Rabbit => &["sounds/rabbit_hop.ogg"],
+            Character::Crocodile => &["sounds/turtle_walk.ogg"],
+            Character::Lizard => &["sounds/lizard_1.ogg", "sounds/lizard_2.ogg"],
+        }
+    }
+
+    pub fn footstep_pitch(&self) -> f32 {
+        match self {
+            Character::Turtle => 1.0,
+            Character::Rabbit => 1.0,
+            Character::Crocodile => 0.75,
+            Character::Lizard => 1.2,
+        }
+    }
+
+    // animation frames on which a foot touches the ground
+    pub fn step_frames(&self) -> &'static [usize] {
+        match self {
+            Character::Turtle => &[0, 2],
+            Character::Rabbit => &[0],
+            Character::Crocodile => &[0, 2],
+            Character::Lizard => &[1, 3],
+        }
+    }
+
+    pub fn mass(&self) -> f32 {
+        match self {
+            Character::Turtle => 3.0,
+            Character::Rabbit => 1.0,
+            Character::Crocodile => 2.5,
+            Character::Lizard => 0.5,
+        }
+    }
+
+    pub fn can_swim(&self) -> bool {
+        match self {
+            Character::Turtle => true,
+            Character::Rabbit => false,
+            Character::Crocodile => true,
+            Character::Lizard => false,
+        }
+    }
+
+    pub fn strength(&self) -> f32 {
+        match self {
+            Character::Turtle => 3.0,
+            Character::Rabbit => 1.0,
+            Character::Crocodile => 2.0,
+            Character::Lizard => 0.5,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
         match self {
             Character::Turtle => 48.0,
-            Character::Rabbit => 16.0 + 96.0 * (time.elapsed_seconds() * 6.0).sin().abs(),
+            Character::Rabbit => 72.0,
             Character::Crocodile => 64.0,
             Character::Lizard => 96.0,
         }
@@ -151,80 +228,113 @@ pub struct DiscoveredCharacters {
     pub discovered: Vec<Character>,
 }
 
+// lets a character walk through solids, only used by the developer console
+#[derive(Component)]
+pub struct NoClip;
+
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub current: CurrentCharacter,
     pub discovered: DiscoveredCharacters,
 }
 
-fn trigger_flag(
+pub fn trigger_push_buttons(
+    time: Res<FixedTime>,
     asset_server: Res<AssetServer>,
-    mut commands: Commands,
-    mut flags: Query<(&CollisionBox, &Transform, &mut GoalFlag)>,
-    mut manager: Query<&mut LevelManager>,
-    characters: Query<(&CollisionBox, &Transform, &Character), Without<GoalFlag>>,
+    mut buttons: Query<(
+        &CollisionBox,
+        &Transform,
+        &mut PushButton,
+        &mut Handle<Image>,
+    )>,
+    characters: Query<(&CollisionBox, &Transform, &Character), Without<PushButton>>,
+    pushables: Query<
+        (&CollisionBox, &Transform),
+        (With<Pushable>, Without<Character>, Without<PushButton>),
+    >,
 ) {
-    for (goal_box, goal_trafo, mut flag) in &mut flags {
-        let mut all_collided = true;
-        for (character_box, character_trafo, _character) in &characters {
-            let character_collider = &character_box
-                .to_collider(character_trafo.translation.x, character_trafo.translation.y);
-            let is_colliding = character_collider.does_collide(
-                &goal_box.to_collider(goal_trafo.translation.x, goal_trafo.translation.y),
-            );
-            if !is_colliding {
-                all_collided = false;
-            }
-        }
-        let prev_reached = flag.reached;
-        if !prev_reached && all_collided {
-            flag.reached = true;
-            commands.spawn(AudioBundle {
-                source: asset_server.load("sounds/win.ogg"),
-                settings: PlaybackSettings {
-                    mode: PlaybackMode::Despawn,
-                    volume: Volume::new_absolute(1.0),
-                    speed: 1.0,
-                    paused: false,
-                },
-            });
-            if let Some(next) = flag.next_level {
-                manager.single_mut().next = Some(next);
+    for (button_box, button_trafo, mut button, mut button_texture) in &mut buttons {
+        let button_collider =
+            button_box.to_collider(button_trafo.translation.x, button_trafo.translation.y);
+        let is_colliding = |body_box: &CollisionBox, body_trafo: &Transform| {
+            body_box
+                .to_collider(body_trafo.translation.x, body_trafo.translation.y)
+                .does_collide(&button_collider)
+        };
+        let colliding_characters: Vec<&Character> = characters
+            .iter()
+            .filter(|(body_box, body_trafo, _)| is_colliding(*body_box, *body_trafo))
+            .map(|(_, _, character)| character)
+            .collect();
+        let colliding_bodies = colliding_characters.len()
+            + pushables
+                .iter()
+                .filter(|(body_box, body_trafo)| is_colliding(*body_box, *body_trafo))
+                .count();
+        let pressed = match button.kind.clone() {
+            SwitchKind::Momentary => colliding_bodies > 0,
+            // levers only change when interacted with
+            SwitchKind::Lever => continue,
+            SwitchKind::Timed { seconds } => {
+                // the countdown alone must not count as a change of the button
+                let button = button.bypass_change_detection();
+                if colliding_bodies > 0 {
+                    button.remaining_seconds = seconds;
+                } else {
+                    button.remaining_seconds -= time.period.as_secs_f32();
+                }
+                button.remaining_seconds > 0.0
             }
+            SwitchKind::Character(required) => colliding_characters.contains(&&required),
+            SwitchKind::Weight { bodies } => colliding_bodies >= bodies,
+        };
+        let prev_pressed = button.pressed;
+        if prev_pressed != pressed {
+            button.pressed = pressed;
+            *button_texture = asset_server.load(button.kind.texture(pressed));
         }
     }
 }
 
-fn trigger_push_buttons(
+pub fn toggle_levers(
     asset_server: Res<AssetServer>,
+    actions: Res<Actions>,
+    player_query: Query<&CurrentCharacter>,
+    characters: Query<(&CollisionBox, &Transform, &Character), Without<PushButton>>,
     mut buttons: Query<(
         &CollisionBox,
         &Transform,
         &mut PushButton,
         &mut Handle<Image>,
     )>,
-    characters: Query<(&CollisionBox, &Transform, &Character), Without<PushButton>>,
 ) {
+    if !actions.current.interact {
+        return;
+    }
+    let Ok(current) = player_query.get_single() else {
+        return;
+    };
+    let Some(character_collider) = characters
+        .iter()
+        .find(|(_, _, character)| **character == current.current)
+        .map(|(collision_box, trafo, _)| {
+            collision_box
+                .to_collider(trafo.translation.x, trafo.translation.y)
+                .grown(INTERACT_MARGIN)
+        })
+    else {
+        return;
+    };
     for (button_box, button_trafo, mut button, mut button_texture) in &mut buttons {
-        let mut any_collided = false;
-        for (character_box, character_trafo, _character) in &characters {
-            let character_collider = &character_box
-                .to_collider(character_trafo.translation.x, character_trafo.translation.y);
-            let is_colliding = character_collider.does_collide(
-                &button_box.to_collider(button_trafo.translation.x, button_trafo.translation.y),
-            );
-            if is_colliding {
-                any_collided = true;
-            }
+        if button.kind != SwitchKind::Lever {
+            continue;
         }
-        let prev_pressed = button.pressed;
-        if prev_pressed != any_collided {
-            button.pressed = any_collided;
-            *button_texture = if any_collided {
-                asset_server.load("tilemap/push_button_pressed.png")
-            } else {
-                asset_server.load("tilemap/push_button.png")
-            };
+        let button_collider =
+            button_box.to_collider(button_trafo.translation.x, button_trafo.translation.y);
+        if character_collider.does_collide(&button_collider) {
+            button.pressed = !button.pressed;
+            *button_texture = asset_server.load(button.kind.texture(button.pressed));
+            info!("Lever {} is now on={}", button.index, button.pressed);
         }
     }
 }
@@ -232,44 +342,30 @@ fn trigger_push_buttons(
 fn switch_characters(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    keys: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut characters: Query<&mut Walking>,
     mut query: Query<(&DiscoveredCharacters, &mut CurrentCharacter)>,
 ) {
-    // switch characters
-    let number_keys = [
-        KeyCode::Key0,
-        KeyCode::Key1,
-        KeyCode::Key2,
-        KeyCode::Key3,
-        KeyCode::Key4,
-        KeyCode::Key5,
-        KeyCode::Key6,
-        KeyCode::Key7,
-        KeyCode::Key8,
-        KeyCode::Key9,
-    ];
-    for (number, key) in number_keys.into_iter().skip(1).enumerate() {
-        if keys.just_pressed(key) {
-            for (discovered, mut current) in &mut query {
-                let selected_character = discovered.discovered.get(number);
-                if let Some(selected) = selected_character {
-                    let prev_character = &current.current;
-                    if prev_character != selected {
-                        commands.spawn(AudioBundle {
-                            source: asset_server.load("sounds/switch_character.ogg"),
-                            settings: PlaybackSettings {
-                                mode: PlaybackMode::Despawn,
-                                volume: Volume::new_absolute(1.0),
-                                speed: 1.0,
-                                paused: false,
-                            },
-                        });
-                        current.current = selected.clone();
-                        for mut walking in &mut characters {
-                            walking.walking = false;
-                        }
-                    }
+    let Some(number) = actions.current.switch_to else {
+        return;
+    };
+    for (discovered, mut current) in &mut query {
+        let selected_character = discovered.discovered.get(number);
+        if let Some(selected) = selected_character {
+            let prev_character = &current.current;
+            if prev_character != selected {
+                commands.spawn(AudioBundle {
+                    source: asset_server.load("sounds/switch_character.ogg"),
+                    settings: PlaybackSettings {
+                        mode: PlaybackMode::Despawn,
+                        volume: Volume::new_absolute(1.0),
+                        speed: 1.0,
+                        paused: false,
+                    },
+                });
+                current.current = selected.clone();
+                for mut walking in &mut characters {
+                    walking.walking = false;
                 }
             }
         }
@@ -319,6 +415,9 @@ pub struct Walking {
     pub walking: bool,
 }
 
+const MEET_MARGIN: f32 = 2.0;
+const INTERACT_MARGIN: f32 = 8.0;
+
 fn trigger_meet_character(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -330,7 +429,10 @@ fn trigger_meet_character(
         for j in i + 1..collidables.len() {
             let collider_a = {
                 let (_, collision_box, transform) = collidables[i];
-                collision_box.to_collider(transform.translation.x, transform.translation.y)
+                // blocking characters only ever touch, so meeting needs some slack
+                collision_box
+                    .to_collider(transform.translation.x, transform.translation.y)
+                    .grown(MEET_MARGIN)
             };
             let collider_b = {
                 let (_, collision_box, transform) = collidables[j];
@@ -351,72 +453,162 @@ fn trigger_meet_character(
     }
 }
 
-fn player_movement(
-    time: Res<Time>,
-    keys: Res<Input<KeyCode>>,
+pub type SolidQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static CollisionBox,
+        &'static Transform,
+        &'static Solid,
+        Option<&'static Pit>,
+    ),
+>;
+
+pub type BodyQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static CollisionBox,
+        &'static mut Transform,
+        Option<&'static Pushable>,
+        Option<&'static Character>,
+    ),
+    Without<Solid>,
+>;
+
+pub fn player_movement(
+    actions: Res<Actions>,
     player_query: Query<&CurrentCharacter>,
-    solid_collider_query: Query<(&CollisionBox, &Transform, &Solid)>,
-    mut query: Query<(&Character, &CollisionBox, &mut Walking, &mut Transform), Without<Solid>>,
+    mut characters: Query<(&Character, &mut Walking, &mut NavigationPath, &mut Movement)>,
 ) {
     if let Ok(current) = player_query.get_single() {
-        for (character, collision_box, mut walking, mut transform) in &mut query {
+        for (character, mut walking, mut path, mut movement) in &mut characters {
             if current.current == *character {
-                let direction = Vec2::new(
-                    if keys.pressed(KeyCode::A) {
-                        -1.0
-                    } else if keys.pressed(KeyCode::D) {
-                        1.0
-                    } else {
-                        0.0
-                    },
-                    if keys.pressed(KeyCode::W) {
-                        1.0
-                    } else if keys.pressed(KeyCode::S) {
-                        -1.0
-                    } else {
-                        0.0
-                    },
-                );
+                let direction = actions.current.movement;
                 if direction.length_squared() == 0.0 {
-                    walking.walking = false;
+                    if path.waypoints.is_empty() {
+                        walking.walking = false;
+                    }
                     continue;
                 }
+                // keyboard input takes over from click-to-move
+                path.waypoints.clear();
                 walking.walking = true;
-                let movement =
-                    direction.normalize_or_zero() * (character.speed(&time) * time.delta_seconds());
-                // high speed leads to glitching because movement code isn't in fixed update
-                transform.translation.x += movement.x;
-                transform.translation.y += movement.y;
-                let character_collider =
-                    collision_box.to_collider(transform.translation.x, transform.translation.y);
-                let mut total_penetration = Vec2::ZERO;
-                solid_collider_query
-                    .iter()
-                    .filter(|(_, _, solid)| solid.whitelisted != Some(character.clone()))
-                    .for_each(|(solid_collision_box, solid_transform, _)| {
-                        let solid_collider = solid_collision_box.to_collider(
-                            solid_transform.translation.x,
-                            solid_transform.translation.y,
-                        );
-
-                        match character_collider.collide(&solid_collider) {
-                            Some(penetration) => {
-                                if penetration.is_finite() {
-                                    total_penetration += penetration;
-                                }
-                            }
-                            None => (),
-                        }
-                    });
-                transform.translation -= total_penetration.extend(0.0);
-                let view_rotation = Vec2::Y.angle_between(movement);
-                if !view_rotation.is_nan() {
-                    transform.rotation = transform.rotation.lerp(
-                        Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, view_rotation),
-                        0.2,
-                    );
-                }
+                movement.intent = direction.normalize_or_zero() * character.speed();
             }
         }
     }
 }
+
+pub fn move_character(
+    entity: Entity,
+    character: &Character,
+    movement: Vec2,
+    solid_collider_query: &SolidQuery,
+    bodies: &mut BodyQuery,
+) -> Vec2 {
+    let character_collider = {
+        let Ok((_, collision_box, mut transform, _, _)) = bodies.get_mut(entity) else {
+            return Vec2::ZERO;
+        };
+        transform.translation.x += movement.x;
+        transform.translation.y += movement.y;
+        collision_box.to_collider(transform.translation.x, transform.translation.y)
+    };
+    push_bodies(
+        entity,
+        character.strength(),
+        &character_collider,
+        solid_collider_query,
+        bodies,
+    );
+    let penetration = total_penetration(
+        &character_collider,
+        solid_colliders(solid_collider_query, Some(character))
+            .into_iter()
+            .chain(body_colliders(bodies, &[entity])),
+    );
+    if let Ok((_, _, mut transform, _, _)) = bodies.get_mut(entity) {
+        transform.translation -= penetration.extend(0.0);
+        let view_rotation = Vec2::Y.angle_between(movement);
+        if !view_rotation.is_nan() {
+            transform.rotation = transform.rotation.lerp(
+                Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, view_rotation),
+                0.2,
+            );
+        }
+    }
+    penetration
+}
+
+fn solid_colliders(
+    solid_collider_query: &SolidQuery,
+    character: Option<&Character>,
+) -> Vec<Collider> {
+    solid_collider_query
+        .iter()
+        // characters pass whitelisted solids, everything else falls into pits instead
+        .filter(|(_, _, solid, pit)| match character {
+            Some(character) => solid.whitelisted.as_ref() != Some(character),
+            None => pit.is_none(),
+        })
+        .map(|(collision_box, transform, _, _)| {
+            collision_box.to_collider(transform.translation.x, transform.translation.y)
+        })
+        .collect()
+}
+
+fn body_colliders(bodies: &BodyQuery, excluded: &[Entity]) -> Vec<Collider> {
+    bodies
+        .iter()
+        .filter(|(entity, _, _, pushable, _)| pushable.is_some() && !excluded.contains(entity))
+        .map(|(_, collision_box, transform, _, _)| {
+            collision_box.to_collider(transform.translation.x, transform.translation.y)
+        })
+        .collect()
+}
+
+fn push_bodies(
+    pusher: Entity,
+    strength: f32,
+    pusher_collider: &Collider,
+    solid_collider_query: &SolidQuery,
+    bodies: &mut BodyQuery,
+) {
+    let pushed: Vec<(Entity, Vec2)> = bodies
+        .iter()
+        .filter(|(entity, _, _, pushable, _)| {
+            *entity != pusher && pushable.is_some_and(|pushable| pushable.mass <= strength)
+        })
+        .filter_map(|(entity, collision_box, transform, _, _)| {
+            pusher_collider
+                .collide(
+                    &collision_box.to_collider(transform.translation.x, transform.translation.y),
+                )
+                .filter(|push| push.is_finite())
+                .map(|push| (entity, push))
+        })
+        .collect();
+    for (entity, push) in pushed {
+        let (pushed_collider, character) = {
+            let Ok((_, collision_box, mut transform, _, character)) = bodies.get_mut(entity) else {
+                continue;
+            };
+            transform.translation += push.extend(0.0);
+            (
+                collision_box.to_collider(transform.translation.x, transform.translation.y),
+                character.cloned(),
+            )
+        };
+        let penetration = total_penetration(
+            &pushed_collider,
+            solid_colliders(solid_collider_query, character.as_ref())
+                .into_iter()
+                .chain(body_colliders(bodies, &[entity, pusher])),
+        );
+        if let Ok((_, _, mut transform, _, _)) = bodies.get_mut(entity) {
+            transform.translation -= penetration.extend(0.0);
+        }
+    }
+}
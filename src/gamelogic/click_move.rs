@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    actions::{ActionSet, Actions},
+    character::{Character, CurrentCharacter},
+    navigation::{Navigation, NavigationPath},
+};
+
+const PREVIEW_TARGET_RADIUS: f32 = 4.0;
+
+pub struct ClickMovePlugin;
+
+impl Plugin for ClickMovePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            click_to_move
+                .after(ActionSet)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            draw_path_preview.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn click_to_move(
+    actions: Res<Actions>,
+    navigation: Navigation,
+    player_query: Query<&CurrentCharacter>,
+    mut characters: Query<(&Character, &Transform, &mut NavigationPath)>,
+) {
+    let (Some(target), Ok(current)) = (actions.current.walk_to, player_query.get_single()) else {
+        return;
+    };
+    for (character, transform, mut path) in &mut characters {
+        if *character != current.current {
+            continue;
+        }
+        match navigation.find_path(transform.translation.truncate(), target, character) {
+            Some(waypoints) => path.waypoints = waypoints,
+            None => info!("No path for {:?} to {:?}", character, target),
+        }
+    }
+}
+
+fn draw_path_preview(
+    mut gizmos: Gizmos,
+    player_query: Query<&CurrentCharacter>,
+    characters: Query<(&Character, &Transform, &NavigationPath)>,
+) {
+    let Ok(current) = player_query.get_single() else {
+        return;
+    };
+    for (character, transform, path) in &characters {
+        if *character != current.current {
+            continue;
+        }
+        if let Some(target) = path.waypoints.last() {
+            let color = character.color();
+            gizmos.linestrip_2d(
+                std::iter::once(transform.translation.truncate())
+                    .chain(path.waypoints.iter().copied()),
+                color,
+            );
+            gizmos.circle_2d(*target, PREVIEW_TARGET_RADIUS, color);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    physics::{Collider, CollisionBox, Pushable, Solid},
+    GameState,
+};
+
+use super::{
+    character::Character,
+    ground::Movement,
+    level::{Actuator, GoalFlag, PushButton},
+    signal::{index_channel, SignalNetwork},
+};
+
+// penetrations are only a few pixels per frame
+const PENETRATION_SCALE: f32 = 8.0;
+const FLAG_MARKER_RADIUS: f32 = 12.0;
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>();
+        app.add_systems(Update, toggle_overlay.run_if(in_state(GameState::InGame)));
+        app.add_systems(
+            Update,
+            (
+                draw_collision_boxes,
+                draw_penetrations,
+                draw_signal_links,
+                draw_flag_overlaps,
+            )
+                .run_if(in_state(GameState::InGame))
+                .run_if(|overlay: Res<DebugOverlay>| overlay.enabled),
+        );
+    }
+}
+
+fn toggle_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.enabled = !overlay.enabled;
+        info!("Debug overlay: {}", overlay.enabled);
+    }
+}
+
+fn draw_collider(gizmos: &mut Gizmos, collider: &Collider, color: Color) {
+    match collider {
+        Collider::Circle(center, radius) => {
+            gizmos.circle_2d(*center, *radius, color);
+        }
+        Collider::AABB(center, size) => {
+            gizmos.rect_2d(*center, 0.0, *size, color);
+        }
+    }
+}
+
+fn draw_collision_boxes(
+    mut gizmos: Gizmos,
+    boxes: Query<(
+        &CollisionBox,
+        &Transform,
+        Option<&Solid>,
+        Option<&Character>,
+        Option<&Pushable>,
+    )>,
+) {
+    for (collision_box, transform, solid, character, pushable) in &boxes {
+        // whitelisted solids take the colour of the character that may pass
+        let color = match (solid, character, pushable) {
+            (
+                Some(Solid {
+                    whitelisted: Some(whitelisted),
+                }),
+                _,
+                _,
+            ) => whitelisted.color(),
+            (Some(_), _, _) => Color::RED,
+            (None, Some(character), _) => character.color(),
+            (None, None, Some(_)) => Color::ORANGE,
+            // everything else only reacts to overlaps
+            (None, None, None) => Color::CYAN,
+        };
+        let collider = collision_box.to_collider(transform.translation.x, transform.translation.y);
+        draw_collider(&mut gizmos, &collider, color);
+    }
+}
+
+fn draw_penetrations(mut gizmos: Gizmos, characters: Query<(&Transform, &Movement)>) {
+    for (transform, movement) in &characters {
+        if movement.penetration == Vec2::ZERO {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        gizmos.line_2d(
+            position,
+            position - movement.penetration * PENETRATION_SCALE,
+            Color::FUCHSIA,
+        );
+    }
+}
+
+fn draw_signal_links(
+    mut gizmos: Gizmos,
+    network: Query<&SignalNetwork>,
+    buttons: Query<(&PushButton, &Transform)>,
+    actuators: Query<(&Actuator, &Transform)>,
+) {
+    let Ok(network) = network.get_single() else {
+        return;
+    };
+    for (button, button_transform) in &buttons {
+        // follow the channel through logic nodes to everything it ends up driving
+        let mut channels = HashSet::new();
+        let mut pending = vec![index_channel(button.index)];
+        while let Some(channel) = pending.pop() {
+            if !channels.insert(channel.clone()) {
+                continue;
+            }
+            for node in &network.nodes {
+                if node.inputs.contains(&channel) {
+                    pending.extend(node.outputs.iter().cloned());
+                }
+            }
+        }
+        for (actuator, actuator_transform) in &actuators {
+            if !channels.contains(&actuator.channel) {
+                continue;
+            }
+            let color = if network.get(&actuator.channel) != actuator.negated {
+                Color::GREEN
+            } else if button.pressed {
+                Color::YELLOW
+            } else {
+                Color::GRAY
+            };
+            gizmos.line_2d(
+                button_transform.translation.truncate(),
+                actuator.origin.truncate(),
+                color,
+            );
+            gizmos.circle_2d(actuator_transform.translation.truncate(), 4.0, color);
+        }
+    }
+}
+
+fn draw_flag_overlaps(
+    mut gizmos: Gizmos,
+    flags: Query<(&GoalFlag, &CollisionBox, &Transform)>,
+    characters: Query<(&Character, &CollisionBox, &Transform), Without<GoalFlag>>,
+) {
+    for (flag, flag_box, flag_transform) in &flags {
+        let flag_position = flag_transform.translation.truncate();
+        let flag_collider = flag_box.to_collider(flag_position.x, flag_position.y);
+        let mut occupied = false;
+        for (character, body_box, body_transform) in &characters {
+            if flag.owner.as_ref().is_some_and(|owner| owner != character) {
+                continue;
+            }
+            let position = body_transform.translation.truncate();
+            if body_box
+                .to_collider(position.x, position.y)
+                .does_collide(&flag_collider)
+            {
+                occupied = true;
+                gizmos.line_2d(position, flag_position, character.color());
+            }
+        }
+        let color = if occupied { Color::GREEN } else { Color::GRAY };
+        gizmos.circle_2d(flag_position, FLAG_MARKER_RADIUS, color);
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::GameState;
+
+use super::{
+    actions::{ActionSet, Actions},
+    character::{Character, CurrentCharacter, DiscoveredCharacters, Walking},
+    navigation::{Navigation, NavigationPath},
+};
+
+const REPATH_SECONDS: f32 = 0.5;
+const FOLLOW_DISTANCE: f32 = 48.0;
+
+#[derive(Resource)]
+pub struct FollowMode {
+    pub enabled: bool,
+    repath: Timer,
+}
+
+impl FollowMode {
+    pub fn reset(&mut self) {
+        self.enabled = false;
+        self.repath.reset();
+    }
+}
+
+pub struct FollowPlugin;
+
+impl Plugin for FollowPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FollowMode {
+            enabled: false,
+            repath: Timer::from_seconds(REPATH_SECONDS, TimerMode::Repeating),
+        });
+        app.add_systems(
+            FixedUpdate,
+            (toggle_follow, update_follow_paths, stop_near_leader)
+                .chain()
+                .after(ActionSet)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn toggle_follow(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    actions: Res<Actions>,
+    mut follow_mode: ResMut<FollowMode>,
+    player_query: Query<&CurrentCharacter>,
+    mut characters: Query<(&Character, &mut Walking, &mut NavigationPath)>,
+) {
+    if actions.current.toggle_follow {
+        follow_mode.enabled = !follow_mode.enabled;
+        info!("Follow mode is now enabled={}", follow_mode.enabled);
+        commands.spawn(AudioBundle {
+            source: asset_server.load("sounds/switch_camera.ogg"),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new_absolute(1.0),
+                speed: 1.0,
+                paused: false,
+            },
+        });
+        if !follow_mode.enabled {
+            let Ok(current) = player_query.get_single() else {
+                return;
+            };
+            for (character, mut walking, mut path) in &mut characters {
+                if *character != current.current {
+                    walking.walking = false;
+                    path.waypoints.clear();
+                }
+            }
+        }
+    }
+}
+
+fn update_follow_paths(
+    time: Res<FixedTime>,
+    navigation: Navigation,
+    mut follow_mode: ResMut<FollowMode>,
+    player_query: Query<(&CurrentCharacter, &DiscoveredCharacters)>,
+    mut characters: Query<(&Character, &Transform, &mut NavigationPath)>,
+) {
+    follow_mode.repath.tick(time.period);
+    if !follow_mode.enabled || !follow_mode.repath.just_finished() {
+        return;
+    }
+    let (Ok((current, discovered)), Some(grid)) = (player_query.get_single(), navigation.grid())
+    else {
+        return;
+    };
+    let Some(leader_position) = characters
+        .iter()
+        .find(|(character, _, _)| **character == current.current)
+        .map(|(_, transform, _)| transform.translation.truncate())
+    else {
+        return;
+    };
+    for (character, transform, mut path) in &mut characters {
+        let position = transform.translation.truncate();
+        if *character == current.current
+            || !discovered.discovered.contains(character)
+            || position.distance(leader_position) < FOLLOW_DISTANCE
+        {
+            continue;
+        }
+        path.waypoints = grid
+            .find_path(position, leader_position, character)
+            .unwrap_or_default();
+    }
+}
+
+fn stop_near_leader(
+    follow_mode: Res<FollowMode>,
+    player_query: Query<&CurrentCharacter>,
+    mut characters: Query<(&Character, &Transform, &mut Walking, &mut NavigationPath)>,
+) {
+    let Ok(current) = player_query.get_single() else {
+        return;
+    };
+    if !follow_mode.enabled {
+        return;
+    }
+    let Some(leader_position) = characters
+        .iter()
+        .find(|(character, _, _, _)| **character == current.current)
+        .map(|(_, transform, _, _)| transform.translation.truncate())
+    else {
+        return;
+    };
+    for (character, transform, mut walking, mut path) in &mut characters {
+        if *character != current.current
+            && !path.waypoints.is_empty()
+            && transform.translation.truncate().distance(leader_position) < FOLLOW_DISTANCE
+        {
+            path.waypoints.clear();
+            walking.walking = false;
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{tilemap::Tilemap, GameState};
+
+use super::{
+    character::{Character, CurrentCharacter, Walking},
+    level::{Actuator, GatedBridge},
+    navigation::{tile_of, Tile},
+};
+
+const PITCH_VARIATION: f32 = 0.08;
+const COMPANION_VOLUME: f32 = 0.4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Surface {
+    Grass,
+    Dirt,
+    Water,
+    Bridge,
+}
+
+impl Surface {
+    pub fn from_texture_name(texture_name: &str) -> Option<Surface> {
+        if texture_name.contains("bridge") {
+            Some(Surface::Bridge)
+        } else if texture_name.contains("water") {
+            Some(Surface::Water)
+        } else if texture_name.contains("dirt") {
+            Some(Surface::Dirt)
+        } else if texture_name.contains("grass")
+            || texture_name.contains("flowers")
+            || texture_name.contains("azalea")
+        {
+            Some(Surface::Grass)
+        } else {
+            None
+        }
+    }
+
+    fn pitch(&self) -> f32 {
+        match self {
+            Surface::Grass => 0.9,
+            Surface::Dirt => 1.0,
+            Surface::Water => 0.8,
+            Surface::Bridge => 1.25,
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        match self {
+            Surface::Grass => 0.6,
+            Surface::Dirt => 1.0,
+            Surface::Water => 0.8,
+            Surface::Bridge => 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SurfaceMap {
+    pub width: usize,
+    pub height: usize,
+    pub surfaces: Vec<Option<Surface>>,
+}
+
+impl SurfaceMap {
+    pub fn new(width: usize, height: usize) -> SurfaceMap {
+        SurfaceMap {
+            width,
+            height,
+            surfaces: vec![None; width * height],
+        }
+    }
+
+    // later layers are drawn on top and therefore win
+    pub fn add_layer(&mut self, layer: &Tilemap) {
+        for y in 0..self.height.min(layer.height()) {
+            for x in 0..self.width.min(layer.width()) {
+                if let Some(surface) = layer
+                    .get_texture_name_of_tile(x, y)
+                    .and_then(Surface::from_texture_name)
+                {
+                    self.surfaces[x + y * self.width] = Some(surface);
+                }
+            }
+        }
+    }
+
+    pub fn surface_at(&self, (x, y): Tile) -> Option<Surface> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            self.surfaces[x as usize + y as usize * self.width]
+        }
+    }
+}
+
+#[derive(Resource)]
+struct FootstepRng {
+    state: u32,
+}
+
+impl FootstepRng {
+    // xorshift, good enough to make steps not sound identical
+    fn next(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state as f32 / u32::MAX as f32
+    }
+}
+
+pub struct FootstepsPlugin;
+
+impl Plugin for FootstepsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FootstepRng { state: 0x9e3779b9 });
+        app.add_systems(Update, play_footsteps.run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn play_footsteps(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut rng: ResMut<FootstepRng>,
+    player_query: Query<&CurrentCharacter>,
+    surface_map: Query<&SurfaceMap>,
+    bridges: Query<(&Transform, &Actuator), With<GatedBridge>>,
+    actuators: Query<(&Transform, &Actuator), Without<GatedBridge>>,
+    characters: Query<
+        (&Character, &Walking, &Transform, &TextureAtlasSprite),
+        Changed<TextureAtlasSprite>,
+    >,
+) {
+    let current = player_query.get_single().ok();
+    for (character, walking, transform, sprite) in &characters {
+        if !walking.walking || !character.step_frames().contains(&sprite.index) {
+            continue;
+        }
+        let tile = tile_of(transform.translation.truncate());
+        let on_bridge = bridges.iter().any(|(bridge_transform, bridge)| {
+            let (bridge_x, bridge_y) = tile_of(bridge_transform.translation.truncate());
+            // bridges span their own and the tile to the right
+            bridge.is_open() && bridge_y == tile.1 && (bridge_x == tile.0 || bridge_x + 1 == tile.0)
+        }) || actuators.iter().any(|(actuator_transform, actuator)| {
+            actuator.is_deck() && tile_of(actuator_transform.translation.truncate()) == tile
+        });
+        let surface = if on_bridge {
+            Some(Surface::Bridge)
+        } else {
+            surface_map
+                .get_single()
+                .ok()
+                .and_then(|surface_map| surface_map.surface_at(tile))
+        };
+        let sounds = character.footstep_sounds();
+        let sound = sounds[(rng.next() * sounds.len() as f32) as usize % sounds.len()];
+        let is_current = current.is_some_and(|current| current.current == *character);
+        let volume = surface.map_or(1.0, |surface| surface.volume())
+            * if is_current { 1.0 } else { COMPANION_VOLUME };
+        let pitch = character.footstep_pitch()
+            * surface.map_or(1.0, |surface| surface.pitch())
+            * (1.0 + (rng.next() * 2.0 - 1.0) * PITCH_VARIATION);
+        commands.spawn(AudioBundle {
+            source: asset_server.load(sound),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new_absolute(volume),
+                speed: pitch,
+                paused: false,
+            },
+        });
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{physics::CollisionBox, GameState};
+
+use super::{
+    budget::BudgetMeter,
+    character::{Character, DiscoveredCharacters},
+    level::{Collectible, GoalFlag},
+    level_mgr::ManagedLevel,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WinCondition {
+    AllDiscoveredAtFlag,
+    CharactersAtFlag(Vec<Character>),
+    AnyCharacterAtFlag,
+    CharactersAtOwnFlags,
+    AllCollected,
+}
+
+// all conditions of a level have to be met at the same time
+#[derive(Component)]
+pub struct LevelGoal {
+    pub conditions: Vec<WinCondition>,
+    pub next_level: Option<ManagedLevel>,
+    pub unmet: Vec<String>,
+    pub completed: bool,
+}
+
+impl LevelGoal {
+    pub fn new(conditions: Vec<WinCondition>, next_level: Option<ManagedLevel>) -> LevelGoal {
+        LevelGoal {
+            conditions,
+            next_level,
+            unmet: Vec::new(),
+            completed: false,
+        }
+    }
+}
+
+pub struct GoalPlugin;
+
+impl Plugin for GoalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (collect_items, check_win_conditions)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn collect_items(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player_query: Query<&DiscoveredCharacters>,
+    mut collectibles: Query<(&CollisionBox, &Transform, &mut Collectible, &mut Visibility)>,
+    characters: Query<(&Character, &CollisionBox, &Transform), Without<Collectible>>,
+) {
+    let Ok(discovered) = player_query.get_single() else {
+        return;
+    };
+    for (item_box, item_trafo, mut collectible, mut visibility) in &mut collectibles {
+        if collectible.collected {
+            continue;
+        }
+        let item_collider =
+            item_box.to_collider(item_trafo.translation.x, item_trafo.translation.y);
+        let collected = characters.iter().any(|(character, body_box, body_trafo)| {
+            discovered.discovered.contains(character)
+                && body_box
+                    .to_collider(body_trafo.translation.x, body_trafo.translation.y)
+                    .does_collide(&item_collider)
+        });
+        if collected {
+            collectible.collected = true;
+            *visibility = Visibility::Hidden;
+            commands.spawn(AudioBundle {
+                source: asset_server.load("sounds/button.ogg"),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::new_absolute(1.0),
+                    speed: 1.8,
+                    paused: false,
+                },
+            });
+        }
+    }
+}
+
+pub fn check_win_conditions(
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    mut goals: Query<&mut LevelGoal>,
+    player_query: Query<&DiscoveredCharacters>,
+    flags: Query<(&CollisionBox, &Transform, &GoalFlag)>,
+    collectibles: Query<&Collectible>,
+    budgets: Query<&BudgetMeter>,
+    characters: Query<(&CollisionBox, &Transform, &Character), Without<GoalFlag>>,
+) {
+    let (Ok(mut goal), Ok(discovered)) = (goals.get_single_mut(), player_query.get_single()) else {
+        return;
+    };
+    if goal.completed || budgets.iter().any(|budget| budget.failed) {
+        return;
+    }
+    let on_flag = |character: &Character, owner: Option<&Character>| {
+        characters
+            .iter()
+            .filter(|(_, _, body)| *body == character)
+            .any(|(body_box, body_trafo, _)| {
+                let body_collider =
+                    body_box.to_collider(body_trafo.translation.x, body_trafo.translation.y);
+                flags
+                    .iter()
+                    .filter(|(_, _, flag)| flag.owner.as_ref() == owner)
+                    .any(|(flag_box, flag_trafo, _)| {
+                        flag_box
+                            .to_collider(flag_trafo.translation.x, flag_trafo.translation.y)
+                            .does_collide(&body_collider)
+                    })
+            })
+    };
+    let collected = collectibles.iter().filter(|item| item.collected).count();
+    let total = collectibles.iter().count();
+    let mut unmet = Vec::new();
+    for condition in &goal.conditions {
+        let missing: Vec<&Character> = match condition {
+            WinCondition::AllDiscoveredAtFlag => discovered
+                .discovered
+                .iter()
+                .filter(|character| !on_flag(character, None))
+                .collect(),
+            WinCondition::CharactersAtFlag(required) => required
+                .iter()
+                .filter(|character| !on_flag(character, None))
+                .collect(),
+            WinCondition::AnyCharacterAtFlag => {
+                if discovered
+                    .discovered
+                    .iter()
+                    .any(|character| on_flag(character, None))
+                {
+                    Vec::new()
+                } else {
+                    discovered.discovered.iter().collect()
+                }
+            }
+            WinCondition::CharactersAtOwnFlags => flags
+                .iter()
+                .filter_map(|(_, _, flag)| flag.owner.as_ref())
+                .filter(|owner| !on_flag(owner, Some(*owner)))
+                .collect(),
+            WinCondition::AllCollected => {
+                if collected < total {
+                    unmet.push(format!("Collect all paw tokens ({}/{})", collected, total));
+                }
+                continue;
+            }
+        };
+        if missing.is_empty() {
+            continue;
+        }
+        let names = missing
+            .iter()
+            .map(|character| format!("{:?}", character))
+            .collect::<Vec<_>>();
+        unmet.push(match condition {
+            WinCondition::AnyCharacterAtFlag => "Anyone to the flag".to_string(),
+            WinCondition::CharactersAtOwnFlags => {
+                format!("{} to their own flag", names.join(", "))
+            }
+            _ => format!("{} to the flag", names.join(", ")),
+        });
+    }
+    if goal.unmet != unmet {
+        goal.unmet = unmet;
+    }
+    if goal.unmet.is_empty() {
+        goal.completed = true;
+        info!("Level goal completed");
+        commands.spawn(AudioBundle {
+            source: asset_server.load("sounds/win.ogg"),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new_absolute(1.0),
+                speed: 1.0,
+                paused: false,
+            },
+        });
+    }
+}
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+use crate::{tilemap::Tilemap, GameState};
+
+use super::{
+    character::{move_character, player_movement, BodyQuery, Character, NoClip, SolidQuery},
+    level_mgr::TILE_SIZE,
+    navigation::{tile_of, walk_paths, Tile},
+};
+
+const MUD_FACTOR: f32 = 0.5;
+const CONVEYOR_SPEED: f32 = 40.0;
+// covering less than this fraction of a step means the character ran into something
+const BLOCKED_FRACTION: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ground {
+    Ice,
+    Mud,
+    Conveyor(Vec2),
+    OneWay(Vec2),
+}
+
+impl Ground {
+    pub fn from_properties(movement: &str, direction: Option<&str>) -> Option<Ground> {
+        let direction = direction.and_then(|direction| match direction {
+            "north" => Some(Vec2::Y),
+            "east" => Some(Vec2::X),
+            "south" => Some(Vec2::NEG_Y),
+            "west" => Some(Vec2::NEG_X),
+            _ => None,
+        });
+        match movement {
+            "ice" => Some(Ground::Ice),
+            "mud" => Some(Ground::Mud),
+            "conveyor" => direction.map(Ground::Conveyor),
+            "one_way" => direction.map(Ground::OneWay),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct GroundMap {
+    pub width: usize,
+    pub height: usize,
+    pub grounds: Vec<Option<Ground>>,
+}
+
+impl GroundMap {
+    pub fn new(width: usize, height: usize) -> GroundMap {
+        GroundMap {
+            width,
+            height,
+            grounds: vec![None; width * height],
+        }
+    }
+
+    pub fn add_layer(&mut self, layer: &Tilemap) {
+        for y in 0..self.height.min(layer.height()) {
+            for x in 0..self.width.min(layer.width()) {
+                if let Some(ground) =
+                    layer
+                        .get_property_of_tile(x, y, "movement")
+                        .and_then(|movement| {
+                            Ground::from_properties(
+                                movement,
+                                layer.get_property_of_tile(x, y, "direction"),
+                            )
+                        })
+                {
+                    self.grounds[x + y * self.width] = Some(ground);
+                }
+            }
+        }
+    }
+
+    pub fn ground_at(&self, (x, y): Tile) -> Option<Ground> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            self.grounds[x as usize + y as usize * self.width]
+        }
+    }
+}
+
+// `intent` is where the character wants to go this frame, `velocity` is kept across frames
+#[derive(Component, Default)]
+pub struct Movement {
+    pub intent: Vec2,
+    pub velocity: Vec2,
+    // how far solids pushed the character back during the last step
+    pub penetration: Vec2,
+}
+
+pub struct GroundPlugin;
+
+impl Plugin for GroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            apply_movement
+                .after(player_movement)
+                .after(walk_paths)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn apply_movement(
+    time: Res<FixedTime>,
+    ground_map: Query<&GroundMap>,
+    solid_collider_query: SolidQuery,
+    mut characters: Query<(Entity, &Character, &mut Movement, Option<&NoClip>)>,
+    mut bodies: BodyQuery,
+) {
+    let ground_map = ground_map.get_single().ok();
+    let ground_at = |position: Vec2| ground_map.and_then(|map| map.ground_at(tile_of(position)));
+    for (entity, character, mut movement, no_clip) in &mut characters {
+        let Ok((_, _, transform, _, _)) = bodies.get(entity) else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        let mut velocity = match ground_at(position) {
+            // ice keeps the momentum until something stops the character
+            Some(Ground::Ice) if movement.velocity != Vec2::ZERO => movement.velocity,
+            Some(Ground::Mud) => movement.intent * MUD_FACTOR,
+            Some(Ground::Conveyor(direction)) => movement.intent + direction * CONVEYOR_SPEED,
+            _ => movement.intent,
+        };
+        let ahead = position + velocity.normalize_or_zero() * TILE_SIZE / 2.0;
+        for ground in [ground_at(position), ground_at(ahead)] {
+            if let Some(Ground::OneWay(direction)) = ground {
+                let against = velocity.dot(direction);
+                if against < 0.0 {
+                    velocity -= direction * against;
+                }
+            }
+        }
+        movement.intent = Vec2::ZERO;
+        if velocity == Vec2::ZERO {
+            movement.velocity = Vec2::ZERO;
+            movement.penetration = Vec2::ZERO;
+            continue;
+        }
+        let step = velocity * time.period.as_secs_f32();
+        if no_clip.is_some() {
+            if let Ok((_, _, mut transform, _, _)) = bodies.get_mut(entity) {
+                transform.translation += step.extend(0.0);
+            }
+            movement.velocity = velocity;
+            movement.penetration = Vec2::ZERO;
+            continue;
+        }
+        // high speed still leads to glitching, the step is not split up
+        movement.penetration =
+            move_character(entity, character, step, &solid_collider_query, &mut bodies);
+        let Ok((_, _, transform, _, _)) = bodies.get(entity) else {
+            continue;
+        };
+        let moved = transform.translation.truncate().distance(position);
+        movement.velocity = if moved < step.length() * BLOCKED_FRACTION {
+            Vec2::ZERO
+        } else {
+            velocity
+        };
+    }
+}
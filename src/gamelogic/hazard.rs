@@ -0,0 +1,178 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{
+    physics::{Collider, CollisionBox},
+    GameState,
+};
+
+use super::{
+    character::{Character, DiscoveredCharacters, Walking},
+    level::{Actuator, Checkpoint, Hazard},
+    navigation::NavigationPath,
+};
+
+const RESPAWN_FADE_SECONDS: f32 = 0.6;
+// characters only fall in once their center is above the hazard
+pub const HAZARD_FOOTPRINT: f32 = 4.0;
+
+#[derive(Component)]
+pub struct RespawnPoint {
+    pub position: Vec2,
+}
+
+#[derive(Component)]
+pub struct RespawnFade {
+    remaining_seconds: f32,
+}
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (trigger_checkpoints, trigger_hazards, fade_respawned)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn trigger_checkpoints(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player_query: Query<&DiscoveredCharacters>,
+    mut checkpoints: Query<(
+        &CollisionBox,
+        &Transform,
+        &mut Checkpoint,
+        &mut Handle<Image>,
+    )>,
+    mut characters: Query<(&Character, &CollisionBox, &Transform, &mut RespawnPoint)>,
+) {
+    let Ok(discovered) = player_query.get_single() else {
+        return;
+    };
+    for (checkpoint_box, checkpoint_trafo, mut checkpoint, mut checkpoint_texture) in
+        &mut checkpoints
+    {
+        let checkpoint_collider = checkpoint_box.to_collider(
+            checkpoint_trafo.translation.x,
+            checkpoint_trafo.translation.y,
+        );
+        let occupied = characters
+            .iter()
+            .any(|(character, body_box, body_trafo, _)| {
+                discovered.discovered.contains(character)
+                    && body_box
+                        .to_collider(body_trafo.translation.x, body_trafo.translation.y)
+                        .does_collide(&checkpoint_collider)
+            });
+        if occupied && !checkpoint.occupied {
+            for (character, _, transform, mut respawn) in &mut characters {
+                if discovered.discovered.contains(character) {
+                    respawn.position = transform.translation.truncate();
+                }
+            }
+            *checkpoint_texture = asset_server.load("tilemap/checkpoint_active.png");
+            commands.spawn(AudioBundle {
+                source: asset_server.load("sounds/new_character.ogg"),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::new_absolute(0.6),
+                    speed: 1.3,
+                    paused: false,
+                },
+            });
+            info!(
+                "Checkpoint at {:?} saved",
+                checkpoint_trafo.translation.truncate()
+            );
+        }
+        if checkpoint.occupied != occupied {
+            checkpoint.occupied = occupied;
+        }
+    }
+}
+
+fn trigger_hazards(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hazards: Query<(&Hazard, &CollisionBox, &Transform)>,
+    decks: Query<(&Actuator, &CollisionBox, &Transform), Without<Character>>,
+    mut characters: Query<
+        (
+            Entity,
+            &Character,
+            &mut Transform,
+            &RespawnPoint,
+            &mut Walking,
+            &mut NavigationPath,
+        ),
+        (Without<Hazard>, Without<RespawnFade>),
+    >,
+) {
+    for (entity, character, mut transform, respawn, mut walking, mut path) in &mut characters {
+        let footprint = Collider::Circle(transform.translation.truncate(), HAZARD_FOOTPRINT);
+        let center = Collider::Circle(transform.translation.truncate(), 0.0);
+        let on_deck = decks.iter().any(|(actuator, deck_box, deck_trafo)| {
+            actuator.is_deck()
+                && deck_box
+                    .to_collider(deck_trafo.translation.x, deck_trafo.translation.y)
+                    .does_collide(&center)
+        });
+        if on_deck {
+            continue;
+        }
+        let hazard = hazards.iter().find(|(hazard, hazard_box, hazard_trafo)| {
+            hazard.kind.is_deadly_for(character)
+                && hazard_box
+                    .to_collider(hazard_trafo.translation.x, hazard_trafo.translation.y)
+                    .does_collide(&footprint)
+        });
+        let Some((hazard, _, _)) = hazard else {
+            continue;
+        };
+        info!(
+            "{:?} ran into {:?}, respawning at {:?}",
+            character, hazard.kind, respawn.position
+        );
+        transform.translation.x = respawn.position.x;
+        transform.translation.y = respawn.position.y;
+        walking.walking = false;
+        path.waypoints.clear();
+        commands.entity(entity).insert(RespawnFade {
+            remaining_seconds: RESPAWN_FADE_SECONDS,
+        });
+        commands.spawn(AudioBundle {
+            source: asset_server.load("sounds/switch_character.ogg"),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new_absolute(1.0),
+                speed: 0.6,
+                paused: false,
+            },
+        });
+    }
+}
+
+fn fade_respawned(
+    mut commands: Commands,
+    time: Res<FixedTime>,
+    mut respawned: Query<(Entity, &mut RespawnFade, &mut TextureAtlasSprite)>,
+) {
+    for (entity, mut fade, mut sprite) in &mut respawned {
+        fade.remaining_seconds -= time.period.as_secs_f32();
+        if fade.remaining_seconds <= 0.0 {
+            sprite.color.set_a(1.0);
+            commands.entity(entity).remove::<RespawnFade>();
+        } else {
+            sprite
+                .color
+                .set_a(1.0 - fade.remaining_seconds / RESPAWN_FADE_SECONDS);
+        }
+    }
+}
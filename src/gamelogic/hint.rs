@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    character::Character,
+    inventory::Item,
+    level::SwitchKind,
+    level_mgr::{HintData, HintTarget, LevelData, TILE_SIZE},
+    solver::{Landmark, LevelModel, Move},
+};
+
+const HIGHLIGHT_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+#[derive(Component)]
+pub struct LevelHints {
+    pub hints: Vec<HintData>,
+    pub revealed: usize,
+    // world positions of the buttons and bridges the hints point at
+    buttons: Vec<Vec2>,
+    bridges: Vec<Vec2>,
+}
+
+impl LevelHints {
+    // levels without written hints get them from their solution
+    pub fn new(data: &LevelData) -> LevelHints {
+        let hints = if data.hints.is_empty() {
+            LevelModel::load(data)
+                .and_then(|mut model| model.solve())
+                .map(|solution| hints_from_moves(data, &solution.moves))
+                .unwrap_or_default()
+        } else {
+            data.hints.clone()
+        };
+        LevelHints {
+            hints,
+            revealed: 0,
+            buttons: data
+                .buttons
+                .iter()
+                .map(|button| Vec2::new(button.position.x, -button.position.y))
+                .collect(),
+            bridges: data
+                .bridges
+                .iter()
+                .map(|bridge| Vec2::new(bridge.position.x, -bridge.position.y))
+                .collect(),
+        }
+    }
+
+    pub fn current(&self) -> Option<&HintData> {
+        self.revealed
+            .checked_sub(1)
+            .and_then(|index| self.hints.get(index))
+    }
+}
+
+// one hint per move, pointing at its button, the bridge on its way or the character it is about
+pub fn hints_from_moves(data: &LevelData, moves: &[Move]) -> Vec<HintData> {
+    moves
+        .iter()
+        .map(|step| {
+            let mover = step.character.name().to_lowercase();
+            let crossed_bridge = data.bridges.iter().position(|bridge| {
+                step.path
+                    .iter()
+                    .any(|point| point.distance(bridge.position) < TILE_SIZE / 2.0)
+            });
+            let (text, target) = match &step.landmark {
+                Landmark::Button(index) => {
+                    let text = if data.buttons[*index].kind == SwitchKind::Lever {
+                        format!("Pull the lever with the {}.", mover)
+                    } else {
+                        format!("Put the {} on the button.", mover)
+                    };
+                    (text, Some(HintTarget::Button(*index)))
+                }
+                Landmark::Start(character) if *character == step.character => (
+                    format!("Take the {} back to where it started.", mover),
+                    None,
+                ),
+                Landmark::Start(character) => (
+                    format!(
+                        "Go and find the {} with the {}.",
+                        character.name().to_lowercase(),
+                        mover
+                    ),
+                    Some(HintTarget::Character(character.clone())),
+                ),
+                Landmark::Flag(None) => (format!("Bring the {} to the flag.", mover), None),
+                Landmark::Flag(Some(_)) => (format!("Bring the {} to its own flag.", mover), None),
+                Landmark::Collectible(_) => {
+                    (format!("Pick up the paw token with the {}.", mover), None)
+                }
+                Landmark::Item(index) => {
+                    let Item::Key(color) = &data.items[*index].item;
+                    let color = format!("{:?}", color).to_lowercase();
+                    (
+                        format!("Pick up the {} key with the {}.", color, mover),
+                        None,
+                    )
+                }
+                Landmark::Teleporter(_) => (format!("Take the {} to the teleporter.", mover), None),
+            };
+            // without a button or character of its own the hint points at the way there
+            let target = target
+                .or(crossed_bridge.map(HintTarget::Bridge))
+                .unwrap_or(HintTarget::Character(step.character.clone()));
+            HintData {
+                text,
+                target: Some(target),
+            }
+        })
+        .collect()
+}
+
+#[derive(Component)]
+struct HintText;
+
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), create_hint_text);
+        app.add_systems(OnExit(GameState::InGame), remove_hint_text);
+        app.add_systems(
+            Update,
+            (reveal_hint, update_hint_text, highlight_hint_target)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn reveal_hint(keys: Res<Input<KeyCode>>, mut hints: Query<&mut LevelHints>) {
+    let Ok(mut hints) = hints.get_single_mut() else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::H) && hints.revealed < hints.hints.len() {
+        hints.revealed += 1;
+    }
+}
+
+fn create_hint_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/NotoSans-Regular.ttf"),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(14.0),
+                left: Val::Px(16.0),
+                ..default()
+            },
+            ..default()
+        },
+        HintText,
+    ));
+}
+
+fn remove_hint_text(mut commands: Commands, query: Query<Entity, With<HintText>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_hint_text(
+    hints: Query<&LevelHints, Changed<LevelHints>>,
+    mut text: Query<&mut Text, With<HintText>>,
+) {
+    let (Ok(hints), Ok(mut text)) = (hints.get_single(), text.get_single_mut()) else {
+        return;
+    };
+    let mut lines: Vec<String> = hints.hints[..hints.revealed]
+        .iter()
+        .enumerate()
+        .map(|(i, hint)| format!("Hint {}: {}", i + 1, hint.text))
+        .collect();
+    if hints.revealed < hints.hints.len() {
+        lines.push(format!(
+            "Press H for a hint ({} left)",
+            hints.hints.len() - hints.revealed
+        ));
+    }
+    text.sections[0].value = lines.join("\n");
+}
+
+// a bouncing arrow above the target of the latest hint
+fn highlight_hint_target(
+    time: Res<Time>,
+    mut gizmos: Gizmos,
+    hints: Query<&LevelHints>,
+    characters: Query<(&Character, &Transform)>,
+) {
+    let Ok(hints) = hints.get_single() else {
+        return;
+    };
+    let Some(target) = hints.current().and_then(|hint| hint.target.as_ref()) else {
+        return;
+    };
+    let position = match target {
+        HintTarget::Button(index) => hints.buttons.get(*index).copied(),
+        HintTarget::Bridge(index) => hints.bridges.get(*index).copied(),
+        HintTarget::Character(character) => characters
+            .iter()
+            .find(|(c, _)| *c == character)
+            .map(|(_, transform)| transform.translation.truncate()),
+    };
+    let Some(position) = position else {
+        return;
+    };
+    let bounce = (time.elapsed_seconds() * 4.0).sin() * 4.0;
+    let tip = position + Vec2::new(0.0, 24.0 + bounce);
+    gizmos.circle_2d(position, 20.0, HIGHLIGHT_COLOR);
+    gizmos.line_2d(tip, tip + Vec2::new(0.0, 24.0), HIGHLIGHT_COLOR);
+    gizmos.line_2d(tip, tip + Vec2::new(-8.0, 8.0), HIGHLIGHT_COLOR);
+    gizmos.line_2d(tip, tip + Vec2::new(8.0, 8.0), HIGHLIGHT_COLOR);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamelogic::level_mgr::ManagedLevel;
+
+    #[test]
+    fn solution_moves_become_hints() {
+        let data = ManagedLevel::Level2.get_data();
+        let solution = LevelModel::load(data)
+            .and_then(|mut model| model.solve())
+            .expect("Level2 can't be solved");
+        let hints = hints_from_moves(data, &solution.moves);
+        let targets: Vec<Option<HintTarget>> =
+            hints.iter().map(|hint| hint.target.clone()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                Some(HintTarget::Character(Character::Rabbit)),
+                Some(HintTarget::Button(0)),
+                Some(HintTarget::Button(1)),
+                Some(HintTarget::Bridge(0)),
+                Some(HintTarget::Character(Character::Turtle)),
+            ]
+        );
+        assert_eq!(hints[1].text, "Put the rabbit on the button.");
+    }
+
+    #[test]
+    fn levels_without_hints_get_them_from_their_solution() {
+        for level in ManagedLevel::levels() {
+            let mut data = level.get_data().clone();
+            data.hints.clear();
+            let hints = LevelHints::new(&data).hints;
+            assert!(!hints.is_empty(), "{:?} got no hints", level);
+            for hint in &hints {
+                let exists = match &hint.target {
+                    Some(HintTarget::Button(index)) => *index < data.buttons.len(),
+                    Some(HintTarget::Bridge(index)) => *index < data.bridges.len(),
+                    Some(HintTarget::Character(character)) => data
+                        .characters
+                        .iter()
+                        .any(|character_data| character_data.character == *character),
+                    None => false,
+                };
+                assert!(exists, "{:?}: {:?} has no target", level, hint.text);
+            }
+        }
+    }
+}
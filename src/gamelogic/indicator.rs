@@ -3,8 +3,11 @@ use bevy::prelude::*;
 use crate::GameState;
 
 use super::{
+    budget::{BudgetMeter, ChallengeMode},
     camera::{CameraMode, CurrentCameraMode},
     character::{Character, CurrentCharacter, DiscoveredCharacters},
+    goal::LevelGoal,
+    inventory::{Inventory, INVENTORY_SLOTS},
 };
 
 #[derive(Component)]
@@ -15,14 +18,36 @@ struct CharacterIndicator {
 #[derive(Component)]
 struct CharacterIndicatorParent;
 
+#[derive(Component)]
+struct InventorySlot {
+    character: Character,
+    index: usize,
+}
+
 #[derive(Component)]
 struct CameraIndicator;
 
+#[derive(Component)]
+struct Overlay;
+
+#[derive(Component)]
+struct GoalIndicator;
+
+#[derive(Component)]
+struct BudgetIndicator;
+
+#[derive(Component)]
+struct BudgetIndicatorFill;
+
+#[derive(Component)]
+struct BudgetIndicatorText;
+
 pub struct IndicatorPlugin;
 
 impl Plugin for IndicatorPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::InGame), create_overlay);
+        app.add_systems(OnExit(GameState::InGame), remove_overlay);
         app.add_systems(
             Update,
             update_character_indicators.run_if(in_state(GameState::InGame)),
@@ -35,6 +60,18 @@ impl Plugin for IndicatorPlugin {
             Update,
             update_camera_indicator.run_if(in_state(GameState::InGame)),
         );
+        app.add_systems(
+            Update,
+            update_goal_indicator.run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            update_budget_indicator.run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            update_inventory_slots.run_if(in_state(GameState::InGame)),
+        );
     }
 }
 
@@ -48,6 +85,75 @@ fn update_camera_indicator(
     }
 }
 
+fn update_goal_indicator(
+    goal: Query<&LevelGoal, Changed<LevelGoal>>,
+    mut query: Query<&mut Text, With<GoalIndicator>>,
+) {
+    if let Ok(goal) = goal.get_single() {
+        for mut text in &mut query {
+            text.sections[0].value = goal.unmet.join("\n");
+        }
+    }
+}
+
+fn update_budget_indicator(
+    challenge: Res<ChallengeMode>,
+    meter: Query<&BudgetMeter>,
+    mut indicator: Query<&mut Style, (With<BudgetIndicator>, Without<BudgetIndicatorFill>)>,
+    mut fill: Query<(&mut Style, &mut BackgroundColor), With<BudgetIndicatorFill>>,
+    mut text: Query<&mut Text, With<BudgetIndicatorText>>,
+) {
+    let meter = meter.get_single().ok().filter(|_| challenge.enabled);
+    for mut style in &mut indicator {
+        style.display = if meter.is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    let Some(meter) = meter else {
+        return;
+    };
+    let remaining = meter.remaining_fraction();
+    for (mut style, mut color) in &mut fill {
+        style.width = Val::Percent(remaining * 100.0);
+        *color = BackgroundColor(Color::hsl(120.0 * remaining, 0.7, 0.5));
+    }
+    for mut text in &mut text {
+        text.sections[0].value = format!(
+            "{} {:.0}/{:.0}",
+            meter.budget.label(),
+            meter.used,
+            meter.budget.limit()
+        );
+    }
+}
+
+fn update_inventory_slots(
+    asset_server: Res<AssetServer>,
+    inventories: Query<(&Character, &Inventory)>,
+    mut slots: Query<(
+        &InventorySlot,
+        &mut UiImage,
+        &mut BackgroundColor,
+        &mut Visibility,
+    )>,
+) {
+    for (slot, mut image, mut color, mut visibility) in &mut slots {
+        let item = inventories
+            .iter()
+            .find(|(character, _)| **character == slot.character)
+            .and_then(|(_, inventory)| inventory.items.get(slot.index));
+        if let Some(item) = item {
+            image.texture = asset_server.load(item.texture());
+            *color = BackgroundColor(item.color());
+            *visibility = Visibility::Inherited;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
 fn update_character_indicators(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -133,7 +239,9 @@ fn make_character_component(
                         visibility: Visibility::Hidden,
                         ..default()
                     },
-                    CharacterIndicator { character },
+                    CharacterIndicator {
+                        character: character.clone(),
+                    },
                 ));
                 p.spawn(ImageBundle {
                     image: UiImage {
@@ -148,6 +256,35 @@ fn make_character_component(
                     ..default()
                 });
             });
+            p.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(4.0),
+                    right: Val::Px(4.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|p| {
+                for index in 0..INVENTORY_SLOTS {
+                    p.spawn((
+                        ImageBundle {
+                            style: Style {
+                                width: Val::Px(12.0),
+                                height: Val::Px(12.0),
+                                ..default()
+                            },
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        InventorySlot {
+                            character: character.clone(),
+                            index,
+                        },
+                    ));
+                }
+            });
             p.spawn(TextBundle {
                 text: Text::from_section(number.to_string(), number_style.clone()),
                 style: Style {
@@ -163,14 +300,17 @@ fn make_character_component(
 
 fn create_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
-        .spawn(NodeBundle {
-            style: Style {
-                width: Val::Percent(100.0),
-                height: Val::Percent(12.0),
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(12.0),
+                    ..default()
+                },
                 ..default()
             },
-            ..default()
-        })
+            Overlay,
+        ))
         .with_children(|p| {
             p.spawn((NodeBundle { ..default() }, CharacterIndicatorParent));
             p.spawn(NodeBundle {
@@ -191,6 +331,85 @@ fn create_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
                 CameraIndicator,
             ));
         });
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/NotoSans-Regular.ttf"),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(16.0),
+                left: Val::Px(16.0),
+                ..default()
+            },
+            ..default()
+        },
+        GoalIndicator,
+        Overlay,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(16.0),
+                    right: Val::Px(16.0),
+                    width: Val::Px(200.0),
+                    flex_direction: FlexDirection::Column,
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            },
+            BudgetIndicator,
+            Overlay,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/NotoSans-Regular.ttf"),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                BudgetIndicatorText,
+            ));
+            p.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+                ..default()
+            })
+            .with_children(|p| {
+                p.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    BudgetIndicatorFill,
+                ));
+            });
+        });
+}
+
+fn remove_overlay(mut commands: Commands, query: Query<Entity, With<Overlay>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
 }
 
 fn update_selected_indicators(
@@ -0,0 +1,227 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{
+    physics::{CollisionBox, Solid},
+    GameState,
+};
+
+use super::{
+    actions::{ActionSet, Actions},
+    character::{Character, CurrentCharacter, DiscoveredCharacters},
+    level::{ItemPickup, LockedGate},
+};
+
+pub const INVENTORY_SLOTS: usize = 3;
+// gates are solid, so characters only ever touch them with a small margin
+const GATE_MARGIN: f32 = 4.0;
+const PASS_DISTANCE: f32 = 48.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyColor {
+    Red,
+    Blue,
+    Yellow,
+    Green,
+}
+
+impl KeyColor {
+    pub fn color(&self) -> Color {
+        match self {
+            KeyColor::Red => Color::hsl(0.0, 0.8, 0.55),
+            KeyColor::Blue => Color::hsl(215.0, 0.8, 0.6),
+            KeyColor::Yellow => Color::hsl(50.0, 0.9, 0.6),
+            KeyColor::Green => Color::hsl(120.0, 0.6, 0.5),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Item {
+    Key(KeyColor),
+}
+
+impl Item {
+    pub fn texture(&self) -> &'static str {
+        match self {
+            Item::Key(_) => "tilemap/key.png",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Item::Key(key) => key.color(),
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Inventory {
+    pub items: Vec<Item>,
+}
+
+impl Inventory {
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= INVENTORY_SLOTS
+    }
+}
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (pick_up_items, unlock_gates, pass_items)
+                .chain()
+                .after(ActionSet)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn play_item_sound(commands: &mut Commands, asset_server: &Res<AssetServer>, speed: f32) {
+    commands.spawn(AudioBundle {
+        source: asset_server.load("sounds/button.ogg"),
+        settings: PlaybackSettings {
+            mode: PlaybackMode::Despawn,
+            volume: Volume::new_absolute(1.0),
+            speed,
+            paused: false,
+        },
+    });
+}
+
+fn pick_up_items(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player_query: Query<&DiscoveredCharacters>,
+    items: Query<(Entity, &ItemPickup, &CollisionBox, &Transform)>,
+    mut characters: Query<
+        (&Character, &CollisionBox, &Transform, &mut Inventory),
+        Without<ItemPickup>,
+    >,
+) {
+    let Ok(discovered) = player_query.get_single() else {
+        return;
+    };
+    for (item_entity, pickup, item_box, item_trafo) in &items {
+        let item_collider =
+            item_box.to_collider(item_trafo.translation.x, item_trafo.translation.y);
+        let taker = characters
+            .iter_mut()
+            .find(|(character, body_box, body_trafo, inventory)| {
+                discovered.discovered.contains(character)
+                    && !inventory.is_full()
+                    && body_box
+                        .to_collider(body_trafo.translation.x, body_trafo.translation.y)
+                        .does_collide(&item_collider)
+            });
+        if let Some((character, _, _, mut inventory)) = taker {
+            info!("{:?} picked up {:?}", character, pickup.item);
+            inventory.items.push(pickup.item);
+            commands.entity(item_entity).despawn_recursive();
+            play_item_sound(&mut commands, &asset_server, 1.5);
+        }
+    }
+}
+
+fn unlock_gates(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut gates: Query<(
+        Entity,
+        &mut LockedGate,
+        &CollisionBox,
+        &Transform,
+        &mut Visibility,
+    )>,
+    mut characters: Query<
+        (&Character, &CollisionBox, &Transform, &mut Inventory),
+        Without<LockedGate>,
+    >,
+) {
+    for (gate_entity, mut gate, gate_box, gate_trafo, mut visibility) in &mut gates {
+        if gate.opened {
+            continue;
+        }
+        let gate_collider = gate_box
+            .to_collider(gate_trafo.translation.x, gate_trafo.translation.y)
+            .grown(GATE_MARGIN);
+        let key = Item::Key(gate.key);
+        let opener = characters
+            .iter_mut()
+            .find(|(_, body_box, body_trafo, inventory)| {
+                inventory.items.contains(&key)
+                    && body_box
+                        .to_collider(body_trafo.translation.x, body_trafo.translation.y)
+                        .does_collide(&gate_collider)
+            });
+        let Some((character, _, _, mut inventory)) = opener else {
+            continue;
+        };
+        if gate.consumes_key {
+            if let Some(index) = inventory.items.iter().position(|item| *item == key) {
+                inventory.items.remove(index);
+            }
+        }
+        info!("{:?} unlocked a {:?} gate", character, gate.key);
+        gate.opened = true;
+        *visibility = Visibility::Hidden;
+        commands.entity(gate_entity).remove::<Solid>();
+        play_item_sound(&mut commands, &asset_server, 0.8);
+    }
+}
+
+fn pass_items(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    actions: Res<Actions>,
+    player_query: Query<(&CurrentCharacter, &DiscoveredCharacters)>,
+    mut characters: Query<(&Character, &Transform, &mut Inventory)>,
+) {
+    if !actions.current.pass_item {
+        return;
+    }
+    let Ok((current, discovered)) = player_query.get_single() else {
+        return;
+    };
+    let Some((giver_position, item)) = characters
+        .iter()
+        .find(|(character, _, _)| **character == current.current)
+        .and_then(|(_, transform, inventory)| {
+            inventory
+                .items
+                .first()
+                .map(|item| (transform.translation.truncate(), *item))
+        })
+    else {
+        return;
+    };
+    let receiver = characters
+        .iter_mut()
+        .filter(|(character, transform, inventory)| {
+            **character != current.current
+                && discovered.discovered.contains(character)
+                && !inventory.is_full()
+                && transform.translation.truncate().distance(giver_position) < PASS_DISTANCE
+        })
+        .min_by(|(_, a, _), (_, b, _)| {
+            let a = a.translation.truncate().distance(giver_position);
+            let b = b.translation.truncate().distance(giver_position);
+            a.total_cmp(&b)
+        });
+    let Some((receiver, _, mut receiver_inventory)) = receiver else {
+        return;
+    };
+    info!("{:?} passed {:?} to {:?}", current.current, item, receiver);
+    receiver_inventory.items.push(item);
+    for (character, _, mut inventory) in &mut characters {
+        if *character == current.current {
+            inventory.items.remove(0);
+        }
+    }
+    play_item_sound(&mut commands, &asset_server, 1.2);
+}
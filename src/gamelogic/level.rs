@@ -5,29 +5,261 @@ use bevy::{
 };
 
 use crate::{
-    physics::{CollisionBox, Solid},
+    physics::{Collider, CollisionBox, Pushable, Solid},
     GameState,
 };
 
-use super::level_mgr::ManagedLevel;
+use super::{
+    character::Character,
+    inventory::{Item, KeyColor},
+    level_mgr::{LevelOverrides, TILE_SIZE},
+    signal::{update_signals, SignalNetwork},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SwitchKind {
+    Momentary,
+    Lever,
+    Timed { seconds: f32 },
+    Character(Character),
+    Weight { bodies: usize },
+}
+
+impl SwitchKind {
+    pub fn texture(&self, pressed: bool) -> &'static str {
+        match (self, pressed) {
+            (SwitchKind::Momentary, false) => "tilemap/push_button.png",
+            (SwitchKind::Momentary, true) => "tilemap/push_button_pressed.png",
+            (SwitchKind::Lever, false) => "tilemap/lever_off.png",
+            (SwitchKind::Lever, true) => "tilemap/lever_on.png",
+            (SwitchKind::Timed { .. }, false) => "tilemap/timed_plate.png",
+            (SwitchKind::Timed { .. }, true) => "tilemap/timed_plate_pressed.png",
+            (SwitchKind::Character(_), false) => "tilemap/character_plate.png",
+            (SwitchKind::Character(_), true) => "tilemap/character_plate_pressed.png",
+            (SwitchKind::Weight { .. }, false) => "tilemap/weight_plate.png",
+            (SwitchKind::Weight { .. }, true) => "tilemap/weight_plate_pressed.png",
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        match self {
+            // several bodies have to fit onto a weight plate at once
+            SwitchKind::Weight { .. } => 16.0,
+            _ => 4.0,
+        }
+    }
+
+    fn sound(&self) -> &'static str {
+        match self {
+            SwitchKind::Momentary => "sounds/button.ogg",
+            SwitchKind::Lever => "sounds/lever.wav",
+            SwitchKind::Timed { .. } => "sounds/timed_switch.wav",
+            SwitchKind::Character(_) => "sounds/character_switch.wav",
+            SwitchKind::Weight { .. } => "sounds/weight_switch.wav",
+        }
+    }
+}
 
 #[derive(Component, Debug)]
 pub struct PushButton {
     pub pressed: bool,
     pub index: usize,
+    pub kind: SwitchKind,
+    pub remaining_seconds: f32,
 }
 
 #[derive(Component, Debug)]
 pub struct GatedBridge {
-    pub opened: bool,
-    pub negated: bool,
     pub index: usize,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActuatorKind {
+    BridgeGate,
+    Door,
+    RetractableWall,
+    Drawbridge,
+    // offset in level pixels the platform travels when open
+    Platform { offset: Vec2 },
+}
+
+impl ActuatorKind {
+    pub fn texture(&self) -> &'static str {
+        match self {
+            ActuatorKind::BridgeGate => "tilemap/bridge_left_gate.png",
+            ActuatorKind::Door => "tilemap/door.png",
+            ActuatorKind::RetractableWall => "tilemap/retractable_wall.png",
+            ActuatorKind::Drawbridge => "tilemap/drawbridge.png",
+            ActuatorKind::Platform { .. } => "tilemap/platform.png",
+        }
+    }
+
+    fn seconds(&self) -> f32 {
+        match self {
+            ActuatorKind::BridgeGate => 0.3,
+            ActuatorKind::Door => 0.5,
+            ActuatorKind::RetractableWall => 0.8,
+            ActuatorKind::Drawbridge => 1.2,
+            ActuatorKind::Platform { offset } => offset.length() / PLATFORM_SPEED,
+        }
+    }
+
+    // platforms carry bodies instead of blocking them
+    pub fn is_blocking(&self) -> bool {
+        !matches!(self, ActuatorKind::Platform { .. })
+    }
+
+    fn sound(&self) -> &'static str {
+        match self {
+            ActuatorKind::Door | ActuatorKind::Drawbridge => "sounds/switch_camera.ogg",
+            _ => "sounds/button.ogg",
+        }
+    }
+
+    fn sound_speed(&self) -> f32 {
+        match self {
+            ActuatorKind::BridgeGate => 1.0,
+            ActuatorKind::Door => 0.7,
+            ActuatorKind::RetractableWall => 0.5,
+            ActuatorKind::Drawbridge => 0.4,
+            ActuatorKind::Platform { .. } => 0.8,
+        }
+    }
+
+    fn animate(&self, progress: f32, origin: Vec3, transform: &mut Transform, sprite: &mut Sprite) {
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        transform.translation = origin;
+        transform.scale = Vec3::ONE;
+        match self {
+            ActuatorKind::BridgeGate => {
+                sprite.color.set_a(1.0 - eased);
+            }
+            ActuatorKind::Door => {
+                // slides into the left frame
+                transform.scale.x = 1.0 - eased * 0.9;
+                transform.translation.x -= eased * 0.45 * TILE_SIZE;
+            }
+            ActuatorKind::RetractableWall => {
+                transform.scale.y = 1.0 - eased * 0.9;
+                transform.translation.y -= eased * 0.45 * TILE_SIZE;
+            }
+            ActuatorKind::Drawbridge => {
+                // raised it only shows its edge, lowered it spans the gap
+                transform.scale.y = 0.2 + eased * 0.8;
+                transform.translation.y += (1.0 - eased) * 0.4 * TILE_SIZE;
+            }
+            ActuatorKind::Platform { offset } => {
+                transform.translation += Vec3::new(offset.x, -offset.y, 0.0) * eased;
+            }
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Actuator {
+    pub kind: ActuatorKind,
+    pub channel: String,
+    pub negated: bool,
+    // 0 is closed, 1 is open
+    pub progress: f32,
+    pub origin: Vec3,
+}
+
+impl Actuator {
+    pub fn new(kind: ActuatorKind, channel: String, negated: bool, origin: Vec3) -> Actuator {
+        Actuator {
+            kind,
+            channel,
+            negated,
+            progress: 0.0,
+            origin,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.progress >= 1.0
+    }
+
+    // lowered drawbridges and platforms are ground, whatever lies below them
+    pub fn is_deck(&self) -> bool {
+        match self.kind {
+            ActuatorKind::Drawbridge => self.is_open(),
+            ActuatorKind::Platform { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Pit {
+    pub filled: bool,
+}
+
+#[derive(Component, Debug)]
+pub struct Teleporter {
+    // both pads of a pair share the same id
+    pub pair: usize,
+    pub channel: Option<String>,
+    pub powered: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HazardKind {
+    DeepWater,
+    Spikes,
+    // unlike a `Pit` it can't be filled and everyone falls in
+    Chasm,
+}
+
+impl HazardKind {
+    // deep water is already drawn by the tilemap
+    pub fn texture(&self) -> Option<&'static str> {
+        match self {
+            HazardKind::DeepWater => None,
+            HazardKind::Spikes => Some("tilemap/spikes.png"),
+            HazardKind::Chasm => Some("tilemap/chasm.png"),
+        }
+    }
+
+    pub fn is_deadly_for(&self, character: &Character) -> bool {
+        match self {
+            HazardKind::DeepWater => !character.can_swim(),
+            HazardKind::Spikes | HazardKind::Chasm => true,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Hazard {
+    pub kind: HazardKind,
+}
+
+#[derive(Component, Debug)]
+pub struct Checkpoint {
+    pub occupied: bool,
+}
+
+#[derive(Component, Debug)]
+pub struct ItemPickup {
+    pub item: Item,
+}
+
+#[derive(Component, Debug)]
+pub struct LockedGate {
+    pub key: KeyColor,
+    pub consumes_key: bool,
+    pub opened: bool,
+}
+
 #[derive(Component)]
 pub struct GoalFlag {
-    pub next_level: Option<ManagedLevel>,
-    pub reached: bool,
+    // flags with an owner only count for that character
+    pub owner: Option<Character>,
+}
+
+#[derive(Component, Debug)]
+pub struct Collectible {
+    pub collected: bool,
 }
 
 #[derive(Bundle)]
@@ -38,15 +270,32 @@ pub struct GoalFlagBundle {
     pub sprite: SpriteBundle,
 }
 
+#[derive(Bundle)]
+pub struct CollectibleBundle {
+    pub collectible: Collectible,
+    pub collision: CollisionBox,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
 #[derive(Bundle)]
 pub struct GatedBridgeBundle {
     pub bridge: GatedBridge,
+    pub actuator: Actuator,
     pub collision: CollisionBox,
     pub solid: Solid,
     #[bundle()]
     pub sprite: SpriteBundle,
 }
 
+#[derive(Bundle)]
+pub struct ActuatorBundle {
+    pub actuator: Actuator,
+    pub collision: CollisionBox,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
 #[derive(Bundle)]
 pub struct PushButtonBundle {
     pub button: PushButton,
@@ -55,67 +304,246 @@ pub struct PushButtonBundle {
     pub sprite: SpriteBundle,
 }
 
+#[derive(Bundle)]
+pub struct TeleporterBundle {
+    pub teleporter: Teleporter,
+    pub collision: CollisionBox,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
+#[derive(Bundle)]
+pub struct HazardBundle {
+    pub hazard: Hazard,
+    pub collision: CollisionBox,
+    #[bundle()]
+    pub spatial: SpatialBundle,
+}
+
+#[derive(Bundle)]
+pub struct CheckpointBundle {
+    pub checkpoint: Checkpoint,
+    pub collision: CollisionBox,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
+#[derive(Bundle)]
+pub struct ItemPickupBundle {
+    pub pickup: ItemPickup,
+    pub collision: CollisionBox,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
+#[derive(Bundle)]
+pub struct LockedGateBundle {
+    pub gate: LockedGate,
+    pub collision: CollisionBox,
+    pub solid: Solid,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
+#[derive(Bundle)]
+pub struct CrateBundle {
+    pub pushable: Pushable,
+    pub collision: CollisionBox,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
+#[derive(Bundle)]
+pub struct PitBundle {
+    pub pit: Pit,
+    pub collision: CollisionBox,
+    pub solid: Solid,
+    #[bundle()]
+    pub sprite: SpriteBundle,
+}
+
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<LevelOverrides>();
         app.add_systems(
             Update,
             play_button_sound.run_if(in_state(GameState::InGame)),
         );
-        app.add_systems(Update, update_bridge.run_if(in_state(GameState::InGame)));
+        app.add_systems(
+            FixedUpdate,
+            update_actuators
+                .after(update_signals)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(FixedUpdate, fill_pits.run_if(in_state(GameState::InGame)));
         app.add_systems(OnEnter(GameState::InGame), start_music);
+        app.add_systems(OnExit(GameState::InGame), stop_music);
     }
 }
 
-fn update_bridge(
+#[derive(Component)]
+struct Music;
+
+const PLATFORM_SPEED: f32 = 48.0;
+// bodies merely touching the edge don't keep an actuator from closing
+const CRUSH_TOLERANCE: f32 = 1.0;
+
+fn update_actuators(
     mut commands: Commands,
-    query: Query<&PushButton>,
-    mut bridge_query: Query<(Entity, &mut Visibility, &mut GatedBridge)>,
+    asset_server: Res<AssetServer>,
+    time: Res<FixedTime>,
+    network: Query<&SignalNetwork>,
+    mut actuators: Query<(
+        Entity,
+        &mut Actuator,
+        &CollisionBox,
+        &mut Transform,
+        &mut Sprite,
+        Option<&Solid>,
+    )>,
+    mut bodies: Query<
+        (&CollisionBox, &mut Transform),
+        (Or<(With<Character>, With<Pushable>)>, Without<Actuator>),
+    >,
 ) {
-    let pressed_indices: HashSet<usize> = query
-        .iter()
-        .filter(|b| b.pressed)
-        .map(|b| b.index)
-        .collect();
-    for (entity, mut visible, mut bridge) in &mut bridge_query {
-        let prev_opened = bridge.opened;
-        let button_pressed = pressed_indices.contains(&bridge.index);
-        let should_be_open = if bridge.negated {
-            !button_pressed
+    let Ok(network) = network.get_single() else {
+        return;
+    };
+    for (entity, mut actuator, collision_box, mut transform, mut sprite, solid) in &mut actuators {
+        let should_be_open = network.get(&actuator.channel) != actuator.negated;
+        let target = if should_be_open { 1.0 } else { 0.0 };
+        if actuator.progress == target {
+            continue;
+        }
+        if !should_be_open && actuator.kind.is_blocking() {
+            let actuator_collider = collision_box.to_collider(actuator.origin.x, actuator.origin.y);
+            let crushing =
+                bodies.iter().any(|(body_box, body_transform)| {
+                    actuator_collider
+                        .collide(&body_box.to_collider(
+                            body_transform.translation.x,
+                            body_transform.translation.y,
+                        ))
+                        .is_some_and(|penetration| penetration.length() > CRUSH_TOLERANCE)
+                });
+            if crushing {
+                continue;
+            }
+        }
+        if actuator.progress == 0.0 || actuator.progress == 1.0 {
+            commands.spawn(AudioBundle {
+                source: asset_server.load(actuator.kind.sound()),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::new_absolute(1.0),
+                    speed: actuator.kind.sound_speed(),
+                    paused: false,
+                },
+            });
+            info!(
+                "Actuator {:?} on channel {} is now opening={}",
+                actuator.kind, actuator.channel, should_be_open
+            );
+        }
+        let step = time.period.as_secs_f32() / actuator.kind.seconds().max(f32::EPSILON);
+        actuator.progress = if should_be_open {
+            (actuator.progress + step).min(1.0)
         } else {
-            button_pressed
+            (actuator.progress - step).max(0.0)
         };
-        if prev_opened != should_be_open {
-            bridge.opened = should_be_open;
-            match should_be_open {
-                true => {
-                    *visible = Visibility::Hidden;
+        let previous = transform.translation;
+        actuator.kind.animate(
+            actuator.progress,
+            actuator.origin,
+            &mut transform,
+            &mut sprite,
+        );
+        if actuator.kind.is_blocking() {
+            match (actuator.is_open(), solid.is_some()) {
+                (true, true) => {
                     commands.entity(entity).remove::<Solid>();
                 }
-                false => {
-                    *visible = Visibility::Visible;
+                (false, false) => {
                     commands.entity(entity).insert(Solid { whitelisted: None });
                 }
+                _ => {}
+            }
+        } else {
+            // carry everything standing on the platform
+            let delta = transform.translation - previous;
+            let platform_collider = collision_box.to_collider(previous.x, previous.y);
+            for (_, mut body_transform) in &mut bodies {
+                let center = Collider::Circle(body_transform.translation.truncate(), 0.0);
+                if platform_collider.does_collide(&center) {
+                    body_transform.translation += delta.truncate().extend(0.0);
+                }
             }
-            info!(
-                "Bridge with index {} is now open={}",
-                bridge.index, should_be_open
-            );
+        }
+    }
+}
+
+const PIT_FILL_DISTANCE: f32 = 8.0;
+
+fn fill_pits(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pits: Query<(Entity, &Transform, &mut Pit, &mut Handle<Image>)>,
+    crates: Query<(Entity, &Transform), (With<Pushable>, Without<Character>)>,
+) {
+    let mut used_crates = HashSet::new();
+    for (pit_entity, pit_trafo, mut pit, mut pit_texture) in &mut pits {
+        if pit.filled {
+            continue;
+        }
+        let filling_crate = crates.iter().find(|(crate_entity, crate_trafo)| {
+            !used_crates.contains(crate_entity)
+                && crate_trafo
+                    .translation
+                    .truncate()
+                    .distance(pit_trafo.translation.truncate())
+                    < PIT_FILL_DISTANCE
+        });
+        if let Some((crate_entity, _)) = filling_crate {
+            used_crates.insert(crate_entity);
+            pit.filled = true;
+            *pit_texture = asset_server.load("tilemap/pit_filled.png");
+            commands.entity(pit_entity).remove::<Solid>();
+            commands.entity(crate_entity).despawn_recursive();
+            commands.spawn(AudioBundle {
+                source: asset_server.load("sounds/button.ogg"),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::new_absolute(1.0),
+                    speed: 1.0,
+                    paused: false,
+                },
+            });
+            info!("Pit at {:?} was filled", pit_trafo.translation.truncate());
         }
     }
 }
 
 fn start_music(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(AudioBundle {
-        source: asset_server.load("sounds/music.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Loop,
-            volume: Volume::new_absolute(1.0),
-            speed: 1.0,
-            paused: false,
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load("sounds/music.ogg"),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Loop,
+                volume: Volume::new_absolute(1.0),
+                speed: 1.0,
+                paused: false,
+            },
         },
-    });
+        Music,
+    ));
+}
+
+fn stop_music(mut commands: Commands, query: Query<Entity, With<Music>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
 }
 
 fn play_button_sound(
@@ -132,7 +560,7 @@ fn play_button_sound(
                     speed: 1.0,
                     paused: false,
                 },
-                source: asset_server.load("sounds/button.ogg"),
+                source: asset_server.load(button_ref.kind.sound()),
             });
         }
     }
@@ -1,24 +1,40 @@
 use std::sync::OnceLock;
 
-use bevy::{prelude::*, render::texture::DEFAULT_IMAGE_HANDLE};
+use bevy::{prelude::*, utils::HashMap};
 
 use crate::{
     loading::TilemapAtlas,
-    physics::{CollisionBox, Solid},
+    physics::{CollisionBox, Pushable, Solid},
     tilemap::{TileSet, Tilemap, TilemapAtlasResolver, Tiles},
+    MainCamera,
 };
 
 use super::{
+    budget::{BudgetMeter, MoveBudget},
     character::{
         AnimationFrames, AnimationTimer, Character, CurrentCharacter, DiscoveredCharacters,
         PlayerBundle, Walking,
     },
+    footsteps::SurfaceMap,
+    goal::{LevelGoal, WinCondition},
+    ground::{GroundMap, Movement},
+    hazard::RespawnPoint,
+    hint::LevelHints,
+    inventory::{Inventory, Item, KeyColor},
     level::{
-        GatedBridge, GatedBridgeBundle, GoalFlag, GoalFlagBundle, PushButton, PushButtonBundle,
+        Actuator, ActuatorBundle, ActuatorKind, Checkpoint, CheckpointBundle, Collectible,
+        CollectibleBundle, CrateBundle, GatedBridge, GatedBridgeBundle, GoalFlag, GoalFlagBundle,
+        Hazard, HazardBundle, HazardKind, ItemPickup, ItemPickupBundle, LockedGate,
+        LockedGateBundle, Pit, PitBundle, PushButton, PushButtonBundle, SwitchKind, Teleporter,
+        TeleporterBundle,
     },
+    navigation::{NavigationPath, NavigationTerrain},
+    rating::LevelStats,
+    signal::{index_channel, Gate, SignalNetwork, SignalNode},
+    validation::validate_level,
 };
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[repr(u8)]
 pub enum ManagedLevel {
     Level1,
@@ -26,11 +42,9 @@ pub enum ManagedLevel {
     Level3,
 }
 
-const COLLIDER_DEBUG: bool = false;
-
 static LEVEL_DATAS: OnceLock<Vec<LevelData>> = OnceLock::new();
 
-const TILE_SIZE: f32 = 32.0;
+pub const TILE_SIZE: f32 = 32.0;
 
 impl ManagedLevel {
     pub fn get_data(&self) -> &'static LevelData {
@@ -38,6 +52,9 @@ impl ManagedLevel {
             vec![
                 LevelData {
                     next_level: Some(ManagedLevel::Level2),
+                    par_seconds: 60.0,
+                    max_switches: 0,
+                    budget: Some(MoveBudget::Distance(640.0)),
                     flag_position: Vec2::new(27.0 * TILE_SIZE, 17.5 * TILE_SIZE),
                     tileset: "levels/tileset.json".to_string(),
                     tilemap_layers: vec![
@@ -45,59 +62,92 @@ impl ManagedLevel {
                         "levels/level1/tilemap_walls.csv".to_string(),
                         "levels/level1/tilemap_deco.csv".to_string(),
                     ],
+                    win_conditions: vec![
+                        WinCondition::CharactersAtFlag(vec![Character::Turtle]),
+                    ],
+                    character_flags: vec![],
+                    collectibles: vec![
+                        CollectibleData {
+                            position: Vec2::new(17.0 * TILE_SIZE, 17.0 * TILE_SIZE),
+                        },
+                        CollectibleData {
+                            position: Vec2::new(24.0 * TILE_SIZE, 21.0 * TILE_SIZE),
+                        },
+                    ],
                     starting_character: Character::Turtle,
-                    characters: vec![CharacterData {
-                        is_discovered: true,
-                        character: Character::Turtle,
-                        starting_position: Vec2::new(18.0 * TILE_SIZE, 18.0 * TILE_SIZE),
-                    }],
+                    characters: vec![
+                        CharacterData {
+                            character: Character::Turtle,
+                            starting_position: Vec2::new(18.0 * TILE_SIZE, 18.0 * TILE_SIZE),
+                            is_discovered: true,
+                        },
+                    ],
                     buttons: vec![],
                     map_colliders: vec![
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 16.0, TILE_SIZE * 16.0),
-                            size: Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 6.2),
+                            corner_position: Vec2::new(16.0 * TILE_SIZE, 16.0 * TILE_SIZE),
+                            size: Vec2::new(0.8 * TILE_SIZE, 6.2 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 16.0, TILE_SIZE * 22.2),
-                            size: Vec2::new(TILE_SIZE * 14.0, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(16.0 * TILE_SIZE, 22.2 * TILE_SIZE),
+                            size: Vec2::new(14.0 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 29.2, TILE_SIZE * 16.0),
-                            size: Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 6.2),
+                            corner_position: Vec2::new(29.2 * TILE_SIZE, 16.0 * TILE_SIZE),
+                            size: Vec2::new(0.8 * TILE_SIZE, 6.2 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 16.8, TILE_SIZE * 16.0),
-                            size: Vec2::new(TILE_SIZE * 12.4, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(16.8 * TILE_SIZE, 16.0 * TILE_SIZE),
+                            size: Vec2::new(12.4 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 20.2, TILE_SIZE * 16.8),
-                            size: Vec2::new(TILE_SIZE * 2.6, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(20.2 * TILE_SIZE, 16.8 * TILE_SIZE),
+                            size: Vec2::new(2.6 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 20.2, TILE_SIZE * 17.8),
-                            size: Vec2::new(TILE_SIZE * 1.6, TILE_SIZE * 2.0),
+                            corner_position: Vec2::new(20.2 * TILE_SIZE, 17.8 * TILE_SIZE),
+                            size: Vec2::new(1.6 * TILE_SIZE, 2.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 23.1, TILE_SIZE * 21.1),
-                            size: Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(23.1 * TILE_SIZE, 21.1 * TILE_SIZE),
+                            size: Vec2::new(0.8 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 25.1, TILE_SIZE * 21.1),
-                            size: Vec2::new(TILE_SIZE * 1.8, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(25.1 * TILE_SIZE, 21.1 * TILE_SIZE),
+                            size: Vec2::new(1.8 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                     ],
                     bridges: vec![],
+                    actuators: vec![],
+                    teleporters: vec![],
+                    hazards: vec![],
+                    checkpoints: vec![],
+                    items: vec![],
+                    locked_gates: vec![],
+                    crates: vec![],
+                    pits: vec![],
+                    character_collisions: false,
+                    signals: vec![],
+                    hints: vec![
+                        HintData {
+                            text: "Walk the turtle to the flag with WASD or by clicking.".to_string(),
+                            target: None,
+                        },
+                    ],
                 },
                 LevelData {
                     next_level: Some(ManagedLevel::Level3),
+                    par_seconds: 90.0,
+                    max_switches: 4,
+                    budget: Some(MoveBudget::Switches(5)),
                     flag_position: Vec2::new(27.0 * TILE_SIZE, 20.0 * TILE_SIZE),
                     tileset: "levels/tileset.json".to_string(),
                     tilemap_layers: vec![
@@ -105,159 +155,170 @@ impl ManagedLevel {
                         "levels/level2/tilemap_walls.csv".to_string(),
                         "levels/level2/tilemap_deco.csv".to_string(),
                     ],
+                    win_conditions: vec![
+                        WinCondition::CharactersAtFlag(vec![Character::Turtle, Character::Rabbit]),
+                    ],
+                    character_flags: vec![],
+                    collectibles: vec![
+                        CollectibleData {
+                            position: Vec2::new(20.0 * TILE_SIZE, 16.0 * TILE_SIZE),
+                        },
+                        CollectibleData {
+                            position: Vec2::new(29.0 * TILE_SIZE, 19.0 * TILE_SIZE),
+                        },
+                    ],
                     starting_character: Character::Turtle,
                     characters: vec![
                         CharacterData {
-                            is_discovered: true,
                             character: Character::Turtle,
                             starting_position: Vec2::new(20.0 * TILE_SIZE, 17.0 * TILE_SIZE),
+                            is_discovered: true,
                         },
                         CharacterData {
-                            is_discovered: false,
                             character: Character::Rabbit,
                             starting_position: Vec2::new(18.0 * TILE_SIZE, 20.0 * TILE_SIZE),
+                            is_discovered: false,
                         },
                     ],
                     buttons: vec![
                         ButtonData {
-                            position: Vec2::new(22.0 * TILE_SIZE, 21.0 * TILE_SIZE),
                             index: 0,
+                            kind: SwitchKind::Momentary,
                             color: Color::rgb(0.8, 0.2, 0.2),
+                            position: Vec2::new(22.0 * TILE_SIZE, 21.0 * TILE_SIZE),
                         },
                         ButtonData {
-                            position: Vec2::new(27.0 * TILE_SIZE, 18.0 * TILE_SIZE),
                             index: 0,
+                            kind: SwitchKind::Momentary,
                             color: Color::rgb(0.8, 0.2, 0.2),
+                            position: Vec2::new(27.0 * TILE_SIZE, 18.0 * TILE_SIZE),
                         },
                     ],
                     map_colliders: vec![
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 16.0, TILE_SIZE * 16.8),
-                            size: Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 4.4),
+                            corner_position: Vec2::new(16.0 * TILE_SIZE, 16.8 * TILE_SIZE),
+                            size: Vec2::new(0.8 * TILE_SIZE, 4.4 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 26.1, TILE_SIZE * 21.1),
-                            size: Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(26.1 * TILE_SIZE, 21.1 * TILE_SIZE),
+                            size: Vec2::new(0.8 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 18.1, TILE_SIZE * 17.1),
-                            size: Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(18.1 * TILE_SIZE, 17.1 * TILE_SIZE),
+                            size: Vec2::new(0.8 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 20.1, TILE_SIZE * 20.9),
-                            size: Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(20.1 * TILE_SIZE, 20.9 * TILE_SIZE),
+                            size: Vec2::new(0.8 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 20.1, TILE_SIZE * 20.1),
-                            size: Vec2::new(TILE_SIZE * 1.8, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(20.1 * TILE_SIZE, 20.1 * TILE_SIZE),
+                            size: Vec2::new(1.8 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 21.1, TILE_SIZE * 19.1),
-                            size: Vec2::new(TILE_SIZE * 3.1, TILE_SIZE * 0.8),
+                            corner_position: Vec2::new(21.1 * TILE_SIZE, 19.1 * TILE_SIZE),
+                            size: Vec2::new(3.1 * TILE_SIZE, 0.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 16.8, TILE_SIZE * 15.8),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(16.8 * TILE_SIZE, 15.8 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 18.8, TILE_SIZE * 14.8),
-                            size: Vec2::new(TILE_SIZE * 3.4, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(18.8 * TILE_SIZE, 14.8 * TILE_SIZE),
+                            size: Vec2::new(3.4 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 22.2, TILE_SIZE * 15.8),
-                            size: Vec2::new(TILE_SIZE * 7.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(22.2 * TILE_SIZE, 15.8 * TILE_SIZE),
+                            size: Vec2::new(7.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 24.2, TILE_SIZE * 16.8),
-                            size: Vec2::new(TILE_SIZE * 1.6, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(24.2 * TILE_SIZE, 16.8 * TILE_SIZE),
+                            size: Vec2::new(1.6 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 24.2, TILE_SIZE * 19.0),
-                            size: Vec2::new(TILE_SIZE * 1.6, TILE_SIZE * 4.0),
+                            corner_position: Vec2::new(24.2 * TILE_SIZE, 19.0 * TILE_SIZE),
+                            size: Vec2::new(1.6 * TILE_SIZE, 4.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 16.8, TILE_SIZE * 21.2),
-                            size: Vec2::new(TILE_SIZE * 1.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(16.8 * TILE_SIZE, 21.2 * TILE_SIZE),
+                            size: Vec2::new(1.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 17.8, TILE_SIZE * 22.2),
-                            size: Vec2::new(TILE_SIZE * 6.4, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(17.8 * TILE_SIZE, 22.2 * TILE_SIZE),
+                            size: Vec2::new(6.4 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 25.8, TILE_SIZE * 22.2),
-                            size: Vec2::new(TILE_SIZE * 3.4, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(25.8 * TILE_SIZE, 22.2 * TILE_SIZE),
+                            size: Vec2::new(3.4 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 29.2, TILE_SIZE * 21.2),
-                            size: Vec2::new(TILE_SIZE * 1.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(29.2 * TILE_SIZE, 21.2 * TILE_SIZE),
+                            size: Vec2::new(1.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 29.2, TILE_SIZE * 16.8),
-                            size: Vec2::new(TILE_SIZE * 1.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(29.2 * TILE_SIZE, 16.8 * TILE_SIZE),
+                            size: Vec2::new(1.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 30.2, TILE_SIZE * 17.8),
-                            size: Vec2::new(TILE_SIZE * 1.0, TILE_SIZE * 3.4),
+                            corner_position: Vec2::new(30.2 * TILE_SIZE, 17.8 * TILE_SIZE),
+                            size: Vec2::new(1.0 * TILE_SIZE, 3.4 * TILE_SIZE),
                         },
                     ],
-                    bridges: vec![BridgeData {
-                        position: Vec2::new(24.0 * TILE_SIZE, 18.0 * TILE_SIZE),
-                        negated: false,
-                        index: 0,
-                        color: Color::rgb(0.8, 0.2, 0.2),
-                    }],
-                },
-                LevelData {
                     bridges: vec![
                         BridgeData {
-                            negated: false,
                             index: 0,
-                            color: Color::rgb(0.2, 0.8, 0.2),
-                            position: Vec2::new(17.0 * TILE_SIZE, 9.0 * TILE_SIZE),
-                        },
-                        BridgeData {
-                            negated: false,
-                            index: 1,
-                            color: Color::rgb(0.8, 0.2, 0.2),
-                            position: Vec2::new(17.0 * TILE_SIZE, 23.0 * TILE_SIZE),
-                        },
-                        BridgeData {
                             negated: false,
-                            index: 1,
                             color: Color::rgb(0.8, 0.2, 0.2),
-                            position: Vec2::new(23.0 * TILE_SIZE, 16.0 * TILE_SIZE),
+                            position: Vec2::new(24.0 * TILE_SIZE, 18.0 * TILE_SIZE),
                         },
                     ],
-                    buttons: vec![
-                        ButtonData {
-                            index: 0,
-                            color: Color::rgb(0.2, 0.8, 0.2),
-                            position: Vec2::new(14.0 * TILE_SIZE, 23.0 * TILE_SIZE),
+                    actuators: vec![],
+                    teleporters: vec![],
+                    hazards: vec![],
+                    checkpoints: vec![],
+                    items: vec![],
+                    locked_gates: vec![],
+                    crates: vec![],
+                    pits: vec![],
+                    character_collisions: false,
+                    signals: vec![],
+                    hints: vec![
+                        HintData {
+                            text: "Someone is hiding to the south-west.".to_string(),
+                            target: Some(HintTarget::Character(Character::Rabbit)),
                         },
-                        ButtonData {
-                            index: 1,
-                            color: Color::rgb(0.8, 0.2, 0.2),
-                            position: Vec2::new(21.0 * TILE_SIZE, 9.0 * TILE_SIZE),
+                        HintData {
+                            text: "The rabbit fits through gaps the turtle can't.".to_string(),
+                            target: Some(HintTarget::Button(0)),
+                        },
+                        HintData {
+                            text: "Either red button opens the bridge, cross it and stand on the other one.".to_string(),
+                            target: Some(HintTarget::Button(1)),
                         },
                     ],
+                },
+                LevelData {
                     next_level: None,
+                    par_seconds: 150.0,
+                    max_switches: 8,
+                    budget: Some(MoveBudget::Tiles(70)),
                     flag_position: Vec2::new(20.0 * TILE_SIZE, 15.0 * TILE_SIZE),
                     tileset: "levels/tileset.json".to_string(),
                     tilemap_layers: vec![
@@ -265,6 +326,18 @@ impl ManagedLevel {
                         "levels/level5/tilemap_walls.csv".to_string(),
                         "levels/level5/tilemap_deco.csv".to_string(),
                     ],
+                    win_conditions: vec![
+                        WinCondition::CharactersAtFlag(vec![Character::Lizard, Character::Turtle, Character::Rabbit]),
+                    ],
+                    character_flags: vec![],
+                    collectibles: vec![
+                        CollectibleData {
+                            position: Vec2::new(13.0 * TILE_SIZE, 24.0 * TILE_SIZE),
+                        },
+                        CollectibleData {
+                            position: Vec2::new(27.0 * TILE_SIZE, 17.0 * TILE_SIZE),
+                        },
+                    ],
                     starting_character: Character::Lizard,
                     characters: vec![
                         CharacterData {
@@ -283,96 +356,162 @@ impl ManagedLevel {
                             is_discovered: true,
                         },
                     ],
+                    buttons: vec![
+                        ButtonData {
+                            index: 0,
+                            kind: SwitchKind::Momentary,
+                            color: Color::rgb(0.2, 0.8, 0.2),
+                            position: Vec2::new(14.0 * TILE_SIZE, 23.0 * TILE_SIZE),
+                        },
+                        ButtonData {
+                            index: 1,
+                            kind: SwitchKind::Momentary,
+                            color: Color::rgb(0.8, 0.2, 0.2),
+                            position: Vec2::new(21.0 * TILE_SIZE, 9.0 * TILE_SIZE),
+                        },
+                    ],
                     map_colliders: vec![
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 15.3, TILE_SIZE * 14.0),
-                            size: Vec2::new(TILE_SIZE * 1.7, TILE_SIZE * 5.0),
+                            corner_position: Vec2::new(15.3 * TILE_SIZE, 14.0 * TILE_SIZE),
+                            size: Vec2::new(1.7 * TILE_SIZE, 5.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 13.0, TILE_SIZE * 14.0),
-                            size: Vec2::new(TILE_SIZE * 1.7, TILE_SIZE * 5.0),
+                            corner_position: Vec2::new(13.0 * TILE_SIZE, 14.0 * TILE_SIZE),
+                            size: Vec2::new(1.7 * TILE_SIZE, 5.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 12.0, TILE_SIZE * 8.0),
-                            size: Vec2::new(TILE_SIZE * 1.0, TILE_SIZE * 17.0),
+                            corner_position: Vec2::new(12.0 * TILE_SIZE, 8.0 * TILE_SIZE),
+                            size: Vec2::new(1.0 * TILE_SIZE, 17.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 23.0, TILE_SIZE * 8.0),
-                            size: Vec2::new(TILE_SIZE * 3.0, TILE_SIZE * 7.0),
+                            corner_position: Vec2::new(23.0 * TILE_SIZE, 8.0 * TILE_SIZE),
+                            size: Vec2::new(3.0 * TILE_SIZE, 7.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 17.0, TILE_SIZE * 10.1),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 12.8),
+                            corner_position: Vec2::new(17.0 * TILE_SIZE, 10.1 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 12.8 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 17.0, TILE_SIZE * 8.0),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 0.9),
+                            corner_position: Vec2::new(17.0 * TILE_SIZE, 8.0 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 0.9 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 23.0, TILE_SIZE * 15.0),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 0.9),
+                            corner_position: Vec2::new(23.0 * TILE_SIZE, 15.0 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 0.9 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 23.0, TILE_SIZE * 17.1),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 0.9),
+                            corner_position: Vec2::new(23.0 * TILE_SIZE, 17.1 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 0.9 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 17.0, TILE_SIZE * 24.1),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 0.9),
+                            corner_position: Vec2::new(17.0 * TILE_SIZE, 24.1 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 0.9 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 26.0, TILE_SIZE * 14.0),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(26.0 * TILE_SIZE, 14.0 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 26.0, TILE_SIZE * 18.0),
-                            size: Vec2::new(TILE_SIZE * 2.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(26.0 * TILE_SIZE, 18.0 * TILE_SIZE),
+                            size: Vec2::new(2.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 28.0, TILE_SIZE * 15.0),
-                            size: Vec2::new(TILE_SIZE * 1.0, TILE_SIZE * 3.0),
+                            corner_position: Vec2::new(28.0 * TILE_SIZE, 15.0 * TILE_SIZE),
+                            size: Vec2::new(1.0 * TILE_SIZE, 3.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: Some(Character::Rabbit),
-                            corner_position: Vec2::new(TILE_SIZE * 19.0, TILE_SIZE * 13.0),
-                            size: Vec2::new(TILE_SIZE * 4.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(19.0 * TILE_SIZE, 13.0 * TILE_SIZE),
+                            size: Vec2::new(4.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 19.0, TILE_SIZE * 17.0),
-                            size: Vec2::new(TILE_SIZE * 1.7, TILE_SIZE * 3.0),
+                            corner_position: Vec2::new(19.0 * TILE_SIZE, 17.0 * TILE_SIZE),
+                            size: Vec2::new(1.7 * TILE_SIZE, 3.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 21.3, TILE_SIZE * 17.0),
-                            size: Vec2::new(TILE_SIZE * 1.7, TILE_SIZE * 3.0),
+                            corner_position: Vec2::new(21.3 * TILE_SIZE, 17.0 * TILE_SIZE),
+                            size: Vec2::new(1.7 * TILE_SIZE, 3.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 23.0, TILE_SIZE * 18.0),
-                            size: Vec2::new(TILE_SIZE * 3.0, TILE_SIZE * 7.0),
+                            corner_position: Vec2::new(23.0 * TILE_SIZE, 18.0 * TILE_SIZE),
+                            size: Vec2::new(3.0 * TILE_SIZE, 7.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 13.0, TILE_SIZE * 25.0),
-                            size: Vec2::new(TILE_SIZE * 12.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(13.0 * TILE_SIZE, 25.0 * TILE_SIZE),
+                            size: Vec2::new(12.0 * TILE_SIZE, 1.0 * TILE_SIZE),
                         },
                         SolidColliderData {
                             whitelisted: None,
-                            corner_position: Vec2::new(TILE_SIZE * 13.0, TILE_SIZE * 7.0),
-                            size: Vec2::new(TILE_SIZE * 12.0, TILE_SIZE * 1.0),
+                            corner_position: Vec2::new(13.0 * TILE_SIZE, 7.0 * TILE_SIZE),
+                            size: Vec2::new(12.0 * TILE_SIZE, 1.0 * TILE_SIZE),
+                        },
+                    ],
+                    bridges: vec![
+                        BridgeData {
+                            index: 0,
+                            negated: false,
+                            color: Color::rgb(0.2, 0.8, 0.2),
+                            position: Vec2::new(17.0 * TILE_SIZE, 9.0 * TILE_SIZE),
+                        },
+                        BridgeData {
+                            index: 1,
+                            negated: false,
+                            color: Color::rgb(0.8, 0.2, 0.2),
+                            position: Vec2::new(17.0 * TILE_SIZE, 23.0 * TILE_SIZE),
+                        },
+                        BridgeData {
+                            index: 1,
+                            negated: false,
+                            color: Color::rgb(0.8, 0.2, 0.2),
+                            position: Vec2::new(23.0 * TILE_SIZE, 16.0 * TILE_SIZE),
+                        },
+                    ],
+                    actuators: vec![],
+                    teleporters: vec![],
+                    hazards: vec![],
+                    checkpoints: vec![],
+                    items: vec![],
+                    locked_gates: vec![],
+                    crates: vec![],
+                    pits: vec![],
+                    character_collisions: false,
+                    signals: vec![],
+                    hints: vec![
+                        HintData {
+                            text: "The lizard can reach the green button on its own.".to_string(),
+                            target: Some(HintTarget::Button(0)),
+                        },
+                        HintData {
+                            text: "While the green button is held, the rabbit can cross the green bridge.".to_string(),
+                            target: Some(HintTarget::Bridge(0)),
+                        },
+                        HintData {
+                            text: "The red button opens both red bridges.".to_string(),
+                            target: Some(HintTarget::Button(1)),
+                        },
+                        HintData {
+                            text: "Somebody is waiting behind the red bridge in the east.".to_string(),
+                            target: Some(HintTarget::Character(Character::Turtle)),
+                        },
+                        HintData {
+                            text: "Bring everyone else to the flag before the rabbit leaves the red button.".to_string(),
+                            target: Some(HintTarget::Character(Character::Rabbit)),
                         },
                     ],
                 },
@@ -380,7 +519,7 @@ impl ManagedLevel {
         })[(*self as u8) as usize]
     }
 
-    pub(crate) fn levels() -> impl Iterator<Item = ManagedLevel> {
+    pub fn levels() -> impl Iterator<Item = ManagedLevel> {
         [
             ManagedLevel::Level1,
             ManagedLevel::Level2,
@@ -390,41 +529,198 @@ impl ManagedLevel {
     }
 }
 
-struct CharacterData {
-    character: Character,
-    starting_position: Vec2,
-    is_discovered: bool,
+#[derive(Clone)]
+pub struct CharacterData {
+    pub character: Character,
+    pub starting_position: Vec2,
+    pub is_discovered: bool,
+}
+
+#[derive(Clone)]
+pub struct ButtonData {
+    pub index: usize,
+    pub kind: SwitchKind,
+    pub color: Color,
+    pub position: Vec2,
+}
+
+#[derive(Clone)]
+pub struct SolidColliderData {
+    pub corner_position: Vec2,
+    pub size: Vec2,
+    pub whitelisted: Option<Character>,
+}
+
+impl SolidColliderData {
+    // the parts of the collider outside of the tile at `tile_position`
+    fn carve(&self, tile_position: Vec2) -> Vec<SolidColliderData> {
+        let min = self.corner_position - TILE_SIZE / 2.0;
+        let max = min + self.size;
+        let hole_min = tile_position - TILE_SIZE / 2.0;
+        let hole_max = tile_position + TILE_SIZE / 2.0;
+        if hole_min.x >= max.x || hole_max.x <= min.x || hole_min.y >= max.y || hole_max.y <= min.y
+        {
+            return vec![self.clone()];
+        }
+        let piece = |min: Vec2, max: Vec2| SolidColliderData {
+            corner_position: min + TILE_SIZE / 2.0,
+            size: max - min,
+            whitelisted: self.whitelisted.clone(),
+        };
+        let mut pieces = Vec::new();
+        if hole_min.x > min.x {
+            pieces.push(piece(min, Vec2::new(hole_min.x, max.y)));
+        }
+        if hole_max.x < max.x {
+            pieces.push(piece(Vec2::new(hole_max.x, min.y), max));
+        }
+        let (left, right) = (min.x.max(hole_min.x), max.x.min(hole_max.x));
+        if hole_min.y > min.y {
+            pieces.push(piece(Vec2::new(left, min.y), Vec2::new(right, hole_min.y)));
+        }
+        if hole_max.y < max.y {
+            pieces.push(piece(Vec2::new(left, hole_max.y), Vec2::new(right, max.y)));
+        }
+        pieces
+    }
+}
+
+#[derive(Clone)]
+pub struct BridgeData {
+    pub index: usize,
+    pub negated: bool,
+    pub color: Color,
+    pub position: Vec2,
+}
+
+#[derive(Clone)]
+pub struct ActuatorData {
+    pub kind: ActuatorKind,
+    pub channel: String,
+    pub negated: bool,
+    pub color: Color,
+    pub position: Vec2,
 }
 
-struct ButtonData {
-    index: usize,
-    color: Color,
-    position: Vec2,
+#[derive(Clone)]
+pub struct TeleporterData {
+    pub pair: usize,
+    pub channel: Option<String>,
+    pub color: Color,
+    pub position: Vec2,
 }
 
-struct SolidColliderData {
-    corner_position: Vec2,
-    size: Vec2,
-    whitelisted: Option<Character>,
+#[derive(Clone)]
+pub struct HazardData {
+    pub kind: HazardKind,
+    pub corner_position: Vec2,
+    pub size: Vec2,
 }
 
-struct BridgeData {
-    index: usize,
-    negated: bool,
-    color: Color,
-    position: Vec2,
+#[derive(Clone)]
+pub struct CheckpointData {
+    pub position: Vec2,
 }
 
+#[derive(Clone)]
+pub struct CharacterFlagData {
+    pub character: Character,
+    pub position: Vec2,
+}
+
+#[derive(Clone)]
+pub struct CollectibleData {
+    pub position: Vec2,
+}
+
+#[derive(Clone)]
+pub struct ItemData {
+    pub item: Item,
+    pub position: Vec2,
+}
+
+#[derive(Clone)]
+pub struct LockedGateData {
+    pub key: KeyColor,
+    pub consumes_key: bool,
+    pub position: Vec2,
+}
+
+#[derive(Clone)]
+pub struct CrateData {
+    pub mass: f32,
+    pub position: Vec2,
+}
+
+#[derive(Clone)]
+pub struct PitData {
+    pub position: Vec2,
+}
+
+// buttons and bridges are counted in the order of the level data
+#[derive(Clone, Debug, PartialEq)]
+pub enum HintTarget {
+    Button(usize),
+    Bridge(usize),
+    Character(Character),
+}
+
+#[derive(Clone)]
+pub struct HintData {
+    pub text: String,
+    pub target: Option<HintTarget>,
+}
+
+#[derive(Clone)]
+pub struct SignalNodeData {
+    pub gate: Gate,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+#[derive(Clone)]
 pub struct LevelData {
-    next_level: Option<ManagedLevel>,
+    pub next_level: Option<ManagedLevel>,
+    pub par_seconds: f32,
+    pub max_switches: usize,
+    pub budget: Option<MoveBudget>,
     pub tileset: String,
     pub tilemap_layers: Vec<String>,
-    flag_position: Vec2,
-    starting_character: Character,
-    characters: Vec<CharacterData>,
-    buttons: Vec<ButtonData>,
-    map_colliders: Vec<SolidColliderData>,
-    bridges: Vec<BridgeData>,
+    pub flag_position: Vec2,
+    pub win_conditions: Vec<WinCondition>,
+    pub character_flags: Vec<CharacterFlagData>,
+    pub collectibles: Vec<CollectibleData>,
+    pub starting_character: Character,
+    pub characters: Vec<CharacterData>,
+    pub buttons: Vec<ButtonData>,
+    pub map_colliders: Vec<SolidColliderData>,
+    pub bridges: Vec<BridgeData>,
+    pub actuators: Vec<ActuatorData>,
+    pub teleporters: Vec<TeleporterData>,
+    pub hazards: Vec<HazardData>,
+    pub checkpoints: Vec<CheckpointData>,
+    pub items: Vec<ItemData>,
+    pub locked_gates: Vec<LockedGateData>,
+    pub crates: Vec<CrateData>,
+    pub pits: Vec<PitData>,
+    pub character_collisions: bool,
+    pub signals: Vec<SignalNodeData>,
+    pub hints: Vec<HintData>,
+}
+
+impl LevelData {
+    // a drawbridge replaces the water or wall on its tile, raised it is solid by itself
+    pub fn map_colliders_around_drawbridges(&self) -> Vec<SolidColliderData> {
+        self.actuators
+            .iter()
+            .filter(|actuator| actuator.kind == ActuatorKind::Drawbridge)
+            .fold(self.map_colliders.clone(), |colliders, drawbridge| {
+                colliders
+                    .iter()
+                    .flat_map(|collider| collider.carve(drawbridge.position))
+                    .collect()
+            })
+    }
 }
 
 #[derive(Component)]
@@ -438,6 +734,12 @@ pub struct LoadedLevel {
     pub level: ManagedLevel,
 }
 
+// edited level data that replaces the built-in data when the level is loaded
+#[derive(Resource, Default)]
+pub struct LevelOverrides {
+    pub levels: HashMap<ManagedLevel, LevelData>,
+}
+
 struct LevelLoadContext<'ctx, 'world, 'cmd> {
     level: ManagedLevel,
     data: &'ctx LevelData,
@@ -458,13 +760,18 @@ impl LevelManager {
         mut atlasses: ResMut<'world, Assets<TextureAtlas>>,
         tiles: Res<'world, Assets<Tiles>>,
         tilesets: Res<'world, Assets<TileSet>>,
+        overrides: &LevelOverrides,
         camera: &mut Transform,
         mut commands: Commands<'world, 'cmd>,
     ) {
         info!("Loading level: {:?}", self.next);
-        let data = self.next.unwrap().get_data();
+        let level = self.next.unwrap();
+        let data = overrides
+            .levels
+            .get(&level)
+            .unwrap_or_else(|| level.get_data());
         let mut ctx = LevelLoadContext {
-            level: self.next.unwrap().clone(),
+            level,
             data,
             asset_server: &asset_server,
             tilemap_atlas: &tilemap_atlas,
@@ -479,7 +786,18 @@ impl LevelManager {
         ctx.create_map_colliders();
         ctx.create_buttons();
         ctx.create_bridges();
+        ctx.create_actuators();
+        ctx.create_teleporters();
+        ctx.create_signal_network();
+        ctx.create_pits();
+        ctx.create_hazards();
+        ctx.create_checkpoints();
+        ctx.create_collectibles();
+        ctx.create_items();
+        ctx.create_locked_gates();
+        ctx.create_crates();
         ctx.create_characters();
+        ctx.create_hints();
     }
 
     pub fn unload_level<'q, I>(&self, commands: &mut Commands, iter: I)
@@ -495,15 +813,91 @@ impl LevelManager {
     }
 }
 
+pub fn level_loading(
+    level_entities: Query<(Entity, &LoadedLevel)>,
+    asset_server: Res<AssetServer>,
+    tilemap_atlas: Res<TilemapAtlas>,
+    atlasses: ResMut<Assets<TextureAtlas>>,
+    tiles: Res<Assets<Tiles>>,
+    tilesets: Res<Assets<TileSet>>,
+    overrides: Res<LevelOverrides>,
+    mut commands: Commands,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+    mut query: Query<&mut LevelManager, Changed<LevelManager>>,
+) {
+    let mut camera = camera.single_mut();
+    if let Ok(mut manager) = query.get_single_mut() {
+        manager.unload_level(&mut commands, level_entities.iter());
+        manager.load_level(
+            asset_server,
+            tilemap_atlas,
+            atlasses,
+            tiles,
+            tilesets,
+            &overrides,
+            &mut camera,
+            commands,
+        );
+        manager.current = manager.next;
+        manager.next = None;
+    }
+}
+
 impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
     fn create_flag(&mut self) {
         let flag_texture = self.asset_server.load("tilemap/flag.png");
         self.commands.spawn((
-            GoalFlagBundle {
-                goal_flag: GoalFlag {
-                    next_level: self.data.next_level.clone(),
-                    reached: false,
+            LevelGoal::new(
+                self.data.win_conditions.clone(),
+                self.data.next_level.clone(),
+            ),
+            LoadedLevel {
+                level: self.level.clone(),
+            },
+        ));
+        self.commands.spawn((
+            LevelStats::new(self.level, self.data.par_seconds, self.data.max_switches),
+            LoadedLevel {
+                level: self.level.clone(),
+            },
+        ));
+        if let Some(budget) = self.data.budget {
+            self.commands.spawn((
+                BudgetMeter::new(budget),
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+        for flag_data in &self.data.character_flags {
+            self.commands.spawn((
+                GoalFlagBundle {
+                    goal_flag: GoalFlag {
+                        owner: Some(flag_data.character.clone()),
+                    },
+                    collision: CollisionBox::Circle { radius: 8.0 },
+                    sprite: SpriteBundle {
+                        sprite: Sprite {
+                            color: flag_data.character.color(),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            flag_data.position.x,
+                            -flag_data.position.y,
+                            5.0,
+                        ),
+                        texture: flag_texture.clone(),
+                        ..default()
+                    },
                 },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+        self.commands.spawn((
+            GoalFlagBundle {
+                goal_flag: GoalFlag { owner: None },
                 collision: CollisionBox::Circle { radius: 8.0 },
                 sprite: SpriteBundle {
                     transform: Transform::from_xyz(
@@ -525,15 +919,20 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
         for bridge_data in &self.data.bridges {
             let bridge_left = self.asset_server.load("tilemap/bridge_left.png");
             let bridge_right = self.asset_server.load("tilemap/bridge_right.png");
-            let gate = self.asset_server.load("tilemap/bridge_left_gate.png");
+            let gate = self.asset_server.load(ActuatorKind::BridgeGate.texture());
+            let origin = Vec3::new(bridge_data.position.x, -bridge_data.position.y, 5.0);
             self.commands
                 .spawn((
                     GatedBridgeBundle {
                         bridge: GatedBridge {
-                            negated: bridge_data.negated,
-                            opened: false,
                             index: bridge_data.index,
                         },
+                        actuator: Actuator::new(
+                            ActuatorKind::BridgeGate,
+                            index_channel(bridge_data.index),
+                            bridge_data.negated,
+                            origin,
+                        ),
                         collision: CollisionBox::AABB {
                             width_radius: 16.0,
                             height_radius: 16.0,
@@ -544,11 +943,7 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
                                 color: bridge_data.color,
                                 ..default()
                             },
-                            transform: Transform::from_xyz(
-                                bridge_data.position.x,
-                                -bridge_data.position.y,
-                                5.0,
-                            ),
+                            transform: Transform::from_translation(origin),
                             texture: gate,
                             ..default()
                         },
@@ -574,18 +969,276 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
         }
     }
 
+    fn create_actuators(&mut self) {
+        for actuator_data in &self.data.actuators {
+            // platforms lie on the ground below everything walking on them
+            let z = if actuator_data.kind.is_blocking() {
+                5.0
+            } else {
+                3.0
+            };
+            let origin = Vec3::new(actuator_data.position.x, -actuator_data.position.y, z);
+            let mut entity = self.commands.spawn((
+                ActuatorBundle {
+                    actuator: Actuator::new(
+                        actuator_data.kind.clone(),
+                        actuator_data.channel.clone(),
+                        actuator_data.negated,
+                        origin,
+                    ),
+                    collision: CollisionBox::AABB {
+                        width_radius: TILE_SIZE,
+                        height_radius: TILE_SIZE,
+                    },
+                    sprite: SpriteBundle {
+                        sprite: Sprite {
+                            color: actuator_data.color,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(origin),
+                        texture: self.asset_server.load(actuator_data.kind.texture()),
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+            if actuator_data.kind.is_blocking() {
+                entity.insert(Solid::default());
+            }
+        }
+    }
+
+    fn create_teleporters(&mut self) {
+        for teleporter_data in &self.data.teleporters {
+            self.commands.spawn((
+                TeleporterBundle {
+                    teleporter: Teleporter {
+                        pair: teleporter_data.pair,
+                        channel: teleporter_data.channel.clone(),
+                        powered: true,
+                    },
+                    collision: CollisionBox::Circle { radius: 8.0 },
+                    sprite: SpriteBundle {
+                        sprite: Sprite {
+                            color: teleporter_data.color,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            teleporter_data.position.x,
+                            -teleporter_data.position.y,
+                            5.0,
+                        ),
+                        texture: self.asset_server.load("tilemap/teleporter.png"),
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+    }
+
+    fn create_hazards(&mut self) {
+        for hazard_data in &self.data.hazards {
+            let center = hazard_data.corner_position + (hazard_data.size - TILE_SIZE) / 2.0;
+            let texture = hazard_data
+                .kind
+                .texture()
+                .map(|texture| self.asset_server.load(texture));
+            self.commands
+                .spawn((
+                    HazardBundle {
+                        hazard: Hazard {
+                            kind: hazard_data.kind,
+                        },
+                        collision: CollisionBox::AABB {
+                            width_radius: hazard_data.size.x,
+                            height_radius: hazard_data.size.y,
+                        },
+                        spatial: SpatialBundle::from_transform(Transform::from_xyz(
+                            center.x, -center.y, 3.0,
+                        )),
+                    },
+                    LoadedLevel {
+                        level: self.level.clone(),
+                    },
+                ))
+                .with_children(|p| {
+                    let Some(texture) = texture else {
+                        return;
+                    };
+                    for x in 0..(hazard_data.size.x / TILE_SIZE) as usize {
+                        for y in 0..(hazard_data.size.y / TILE_SIZE) as usize {
+                            let offset = hazard_data.corner_position
+                                + Vec2::new(x as f32, y as f32) * TILE_SIZE
+                                - center;
+                            p.spawn(SpriteBundle {
+                                texture: texture.clone(),
+                                transform: Transform::from_xyz(offset.x, -offset.y, 0.0),
+                                ..default()
+                            });
+                        }
+                    }
+                });
+        }
+    }
+
+    fn create_checkpoints(&mut self) {
+        for checkpoint_data in &self.data.checkpoints {
+            self.commands.spawn((
+                CheckpointBundle {
+                    checkpoint: Checkpoint { occupied: false },
+                    collision: CollisionBox::Circle { radius: 8.0 },
+                    sprite: SpriteBundle {
+                        transform: Transform::from_xyz(
+                            checkpoint_data.position.x,
+                            -checkpoint_data.position.y,
+                            5.0,
+                        ),
+                        texture: self.asset_server.load("tilemap/checkpoint.png"),
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+    }
+
+    fn create_collectibles(&mut self) {
+        for collectible_data in &self.data.collectibles {
+            self.commands.spawn((
+                CollectibleBundle {
+                    collectible: Collectible { collected: false },
+                    collision: CollisionBox::Circle { radius: 8.0 },
+                    sprite: SpriteBundle {
+                        transform: Transform::from_xyz(
+                            collectible_data.position.x,
+                            -collectible_data.position.y,
+                            5.0,
+                        ),
+                        texture: self.asset_server.load("tilemap/paw_token.png"),
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+    }
+
+    fn create_items(&mut self) {
+        for item_data in &self.data.items {
+            self.commands.spawn((
+                ItemPickupBundle {
+                    pickup: ItemPickup {
+                        item: item_data.item,
+                    },
+                    collision: CollisionBox::Circle { radius: 8.0 },
+                    sprite: SpriteBundle {
+                        sprite: Sprite {
+                            color: item_data.item.color(),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            item_data.position.x,
+                            -item_data.position.y,
+                            5.0,
+                        ),
+                        texture: self.asset_server.load(item_data.item.texture()),
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+    }
+
+    fn create_locked_gates(&mut self) {
+        for gate_data in &self.data.locked_gates {
+            self.commands.spawn((
+                LockedGateBundle {
+                    gate: LockedGate {
+                        key: gate_data.key,
+                        consumes_key: gate_data.consumes_key,
+                        opened: false,
+                    },
+                    collision: CollisionBox::AABB {
+                        width_radius: TILE_SIZE,
+                        height_radius: TILE_SIZE,
+                    },
+                    solid: Solid::default(),
+                    sprite: SpriteBundle {
+                        sprite: Sprite {
+                            color: gate_data.key.color(),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            gate_data.position.x,
+                            -gate_data.position.y,
+                            5.0,
+                        ),
+                        texture: self.asset_server.load("tilemap/locked_gate.png"),
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+    }
+
+    fn create_signal_network(&mut self) {
+        self.commands.spawn((
+            SignalNetwork {
+                nodes: self
+                    .data
+                    .signals
+                    .iter()
+                    .map(|node| {
+                        SignalNode::new(
+                            node.gate.clone(),
+                            node.inputs.clone(),
+                            node.outputs.clone(),
+                        )
+                    })
+                    .collect(),
+                ..default()
+            },
+            LoadedLevel {
+                level: self.level.clone(),
+            },
+        ));
+    }
+
     fn create_buttons(&mut self) {
         for button_data in &self.data.buttons {
             let button_base = self.asset_server.load("tilemap/push_button_base.png");
-            let button = self.asset_server.load("tilemap/push_button.png");
+            let button = self.asset_server.load(button_data.kind.texture(false));
+            let face = match &button_data.kind {
+                SwitchKind::Character(character) => Some(character.face_texture(self.asset_server)),
+                _ => None,
+            };
             self.commands
                 .spawn((
                     PushButtonBundle {
                         button: PushButton {
                             pressed: false,
                             index: button_data.index,
+                            kind: button_data.kind.clone(),
+                            remaining_seconds: 0.0,
+                        },
+                        collision: CollisionBox::Circle {
+                            radius: button_data.kind.radius(),
                         },
-                        collision: CollisionBox::Circle { radius: 4.0 },
                         sprite: SpriteBundle {
                             transform: Transform::from_xyz(
                                 button_data.position.x,
@@ -605,17 +1258,90 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
                     },
                 ))
                 .with_children(|p| {
-                    p.spawn(SpriteBundle {
-                        texture: button_base,
-                        transform: Transform::from_xyz(0.0, 0.0, -1.0),
-                        ..default()
-                    });
+                    if button_data.kind == SwitchKind::Momentary {
+                        p.spawn(SpriteBundle {
+                            texture: button_base,
+                            transform: Transform::from_xyz(0.0, 0.0, -1.0),
+                            ..default()
+                        });
+                    }
+                    if let Some(face) = face {
+                        p.spawn(SpriteBundle {
+                            texture: face,
+                            transform: Transform::from_xyz(0.0, 0.0, 1.0)
+                                .with_scale(Vec3::new(0.5, 0.5, 1.0)),
+                            ..default()
+                        });
+                    }
                 });
         }
     }
 
+    fn create_crates(&mut self) {
+        for crate_data in &self.data.crates {
+            let texture = if crate_data.mass > 1.0 {
+                self.asset_server.load("tilemap/crate_heavy.png")
+            } else {
+                self.asset_server.load("tilemap/crate.png")
+            };
+            self.commands.spawn((
+                CrateBundle {
+                    pushable: Pushable {
+                        mass: crate_data.mass,
+                    },
+                    collision: CollisionBox::AABB {
+                        width_radius: 28.0,
+                        height_radius: 28.0,
+                    },
+                    sprite: SpriteBundle {
+                        transform: Transform::from_xyz(
+                            crate_data.position.x,
+                            -crate_data.position.y,
+                            6.0,
+                        ),
+                        texture,
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+    }
+
+    fn create_pits(&mut self) {
+        for pit_data in &self.data.pits {
+            self.commands.spawn((
+                PitBundle {
+                    pit: Pit { filled: false },
+                    collision: CollisionBox::AABB {
+                        width_radius: TILE_SIZE,
+                        height_radius: TILE_SIZE,
+                    },
+                    solid: Solid::default(),
+                    sprite: SpriteBundle {
+                        transform: Transform::from_xyz(
+                            pit_data.position.x,
+                            -pit_data.position.y,
+                            3.0,
+                        ),
+                        texture: self.asset_server.load("tilemap/pit.png"),
+                        ..default()
+                    },
+                },
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+    }
+
     fn create_tilemap(&mut self) {
         let tile_set_asset: Handle<TileSet> = self.asset_server.load(&self.data.tileset);
+        let mut navigation_terrain = None;
+        let mut surface_map = None;
+        let mut ground_map = None;
         for (layer_index, tilemap_layer) in self.data.tilemap_layers.iter().enumerate() {
             let tiles_asset: Handle<Tiles> = self.asset_server.load(tilemap_layer);
             let tilemap = Tilemap::new(
@@ -630,11 +1356,49 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
                 self.atlasses,
             );
             spawn_tilemap(&tilemap_resolver, layer_index, &self.level, self.commands);
+            if layer_index == 0 {
+                navigation_terrain = Some(NavigationTerrain::from_ground(&tilemap));
+                if cfg!(debug_assertions) {
+                    for issue in validate_level(self.data, &tilemap) {
+                        warn!("{:?}: {}", self.level, issue);
+                    }
+                }
+            }
+            surface_map
+                .get_or_insert_with(|| SurfaceMap::new(tilemap.width(), tilemap.height()))
+                .add_layer(&tilemap);
+            ground_map
+                .get_or_insert_with(|| GroundMap::new(tilemap.width(), tilemap.height()))
+                .add_layer(&tilemap);
+        }
+        if let Some(navigation_terrain) = navigation_terrain {
+            self.commands.spawn((
+                navigation_terrain,
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+        if let Some(surface_map) = surface_map {
+            self.commands.spawn((
+                surface_map,
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
+        }
+        if let Some(ground_map) = ground_map {
+            self.commands.spawn((
+                ground_map,
+                LoadedLevel {
+                    level: self.level.clone(),
+                },
+            ));
         }
     }
 
     fn create_map_colliders(&mut self) {
-        for map_collider in &self.data.map_colliders {
+        for map_collider in &self.data.map_colliders_around_drawbridges() {
             self.commands.spawn((
                 CollisionBox::AABB {
                     width_radius: map_collider.size.x,
@@ -643,29 +1407,11 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
                 Solid {
                     whitelisted: map_collider.whitelisted.clone(),
                 },
-                SpriteBundle {
-                    texture: DEFAULT_IMAGE_HANDLE.typed(),
-                    transform: Transform::from_xyz(
-                        map_collider.corner_position.x + (map_collider.size.x - TILE_SIZE) / 2.0,
-                        -(map_collider.corner_position.y + (map_collider.size.y - TILE_SIZE) / 2.0),
-                        4.0,
-                    )
-                    .with_scale(Vec3::new(
-                        map_collider.size.x,
-                        map_collider.size.y,
-                        0.0,
-                    )),
-                    sprite: Sprite {
-                        color: Color::rgba(1.0, 0.0, 0.0, 0.5),
-                        ..default()
-                    },
-                    visibility: if COLLIDER_DEBUG {
-                        Visibility::Visible
-                    } else {
-                        Visibility::Hidden
-                    },
-                    ..default()
-                },
+                SpatialBundle::from_transform(Transform::from_xyz(
+                    map_collider.corner_position.x + (map_collider.size.x - TILE_SIZE) / 2.0,
+                    -(map_collider.corner_position.y + (map_collider.size.y - TILE_SIZE) / 2.0),
+                    4.0,
+                )),
                 LoadedLevel {
                     level: self.level.clone(),
                 },
@@ -673,6 +1419,15 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
         }
     }
 
+    fn create_hints(&mut self) {
+        self.commands.spawn((
+            LevelHints::new(self.data),
+            LoadedLevel {
+                level: self.level.clone(),
+            },
+        ));
+    }
+
     fn create_characters(&mut self) {
         let mut discovered = Vec::new();
         for character in &self.data.characters {
@@ -683,7 +1438,7 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
                 self.camera.translation.x = character.starting_position.x;
                 self.camera.translation.y = -character.starting_position.y;
             }
-            self.commands.spawn((
+            let mut entity = self.commands.spawn((
                 SpriteSheetBundle {
                     texture_atlas: character
                         .character
@@ -697,6 +1452,15 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
                     ..default()
                 },
                 Walking { walking: false },
+                NavigationPath::default(),
+                Movement::default(),
+                Inventory::default(),
+                RespawnPoint {
+                    position: Vec2::new(
+                        character.starting_position.x,
+                        -character.starting_position.y,
+                    ),
+                },
                 AnimationFrames {
                     frames: character.character.frames(),
                 },
@@ -707,6 +1471,11 @@ impl<'ctx, 'world, 'cmd> LevelLoadContext<'ctx, 'world, 'cmd> {
                     level: self.level.clone(),
                 },
             ));
+            if self.data.character_collisions {
+                entity.insert(Pushable {
+                    mass: character.character.mass(),
+                });
+            }
         }
         self.commands.spawn((
             PlayerBundle {
@@ -753,3 +1522,41 @@ fn spawn_tilemap(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(colliders: &[SolidColliderData]) -> Vec<(Vec2, Vec2)> {
+        colliders
+            .iter()
+            .map(|collider| {
+                (
+                    collider.corner_position / TILE_SIZE,
+                    collider.size / TILE_SIZE,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drawbridges_cut_their_tile_out_of_map_colliders() {
+        let water = SolidColliderData {
+            corner_position: Vec2::new(2.0, 3.0) * TILE_SIZE,
+            size: Vec2::new(3.0, 2.0) * TILE_SIZE,
+            whitelisted: None,
+        };
+        assert_eq!(
+            bounds(&water.carve(Vec2::new(3.0, 3.0) * TILE_SIZE)),
+            [
+                (Vec2::new(2.0, 3.0), Vec2::new(1.0, 2.0)),
+                (Vec2::new(4.0, 3.0), Vec2::new(1.0, 2.0)),
+                (Vec2::new(3.0, 4.0), Vec2::new(1.0, 1.0)),
+            ]
+        );
+        assert_eq!(
+            bounds(&water.carve(Vec2::new(6.0, 3.0) * TILE_SIZE)),
+            bounds(&[water])
+        );
+    }
+}
@@ -1,15 +1,38 @@
 use bevy::{app::PluginGroupBuilder, prelude::*};
 
 use self::{
-    camera::CameraControlPlugin, character::CharacterPlugin, indicator::IndicatorPlugin,
-    level::LevelPlugin,
+    actions::ActionsPlugin, budget::BudgetPlugin, camera::CameraControlPlugin,
+    character::CharacterPlugin, click_move::ClickMovePlugin, debug_overlay::DebugOverlayPlugin,
+    follow::FollowPlugin, footsteps::FootstepsPlugin, goal::GoalPlugin, ground::GroundPlugin,
+    hazard::HazardPlugin, hint::HintPlugin, indicator::IndicatorPlugin, inventory::InventoryPlugin,
+    level::LevelPlugin, navigation::NavigationPlugin, rating::RatingPlugin, replay::ReplayPlugin,
+    signal::SignalPlugin, speedrun::SpeedrunPlugin, teleport::TeleportPlugin,
 };
 
+pub mod actions;
+pub mod budget;
 mod camera;
 pub mod character;
+mod click_move;
+pub mod debug_overlay;
+mod follow;
+mod footsteps;
+pub mod goal;
+pub mod ground;
+mod hazard;
+pub mod hint;
 mod indicator;
-mod level;
+pub mod inventory;
+pub mod level;
 pub mod level_mgr;
+pub mod navigation;
+pub mod rating;
+pub mod replay;
+pub mod signal;
+pub mod solver;
+pub mod speedrun;
+mod teleport;
+pub mod validation;
 
 pub struct GameLogicPlugins;
 
@@ -17,9 +40,26 @@ impl PluginGroup for GameLogicPlugins {
     fn build(self) -> PluginGroupBuilder {
         let group = PluginGroupBuilder::start::<Self>();
         group
+            .add(ActionsPlugin)
+            .add(ReplayPlugin)
             .add(LevelPlugin)
             .add(CharacterPlugin)
             .add(CameraControlPlugin)
             .add(IndicatorPlugin)
+            .add(NavigationPlugin)
+            .add(FollowPlugin)
+            .add(ClickMovePlugin)
+            .add(FootstepsPlugin)
+            .add(SignalPlugin)
+            .add(TeleportPlugin)
+            .add(HazardPlugin)
+            .add(GoalPlugin)
+            .add(HintPlugin)
+            .add(RatingPlugin)
+            .add(SpeedrunPlugin)
+            .add(BudgetPlugin)
+            .add(GroundPlugin)
+            .add(InventoryPlugin)
+            .add(DebugOverlayPlugin)
     }
 }
@@ -0,0 +1,315 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+
+use crate::{
+    physics::{Collider, CollisionBox, Solid},
+    tilemap::Tilemap,
+    GameState,
+};
+
+use super::{
+    character::{player_movement, Character, Walking},
+    ground::Movement,
+    level::{Actuator, Hazard, HazardKind},
+    level_mgr::TILE_SIZE,
+};
+
+// cells are checked with a slightly smaller body so that narrow corridors stay open
+const CELL_CLEARANCE: f32 = TILE_SIZE * 0.4;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+const WAYPOINT_DISTANCE: f32 = 4.0;
+// fraction of a step that has to be covered, otherwise the path is considered blocked
+const MIN_PROGRESS: f32 = 0.1;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            walk_paths
+                .after(player_movement)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Component, Default)]
+pub struct NavigationPath {
+    pub waypoints: Vec<Vec2>,
+    // position and planned step of the previous frame
+    last_step: Option<(Vec2, f32)>,
+}
+
+#[derive(SystemParam)]
+pub struct Navigation<'w, 's> {
+    terrain: Query<'w, 's, &'static NavigationTerrain>,
+    solids: Query<'w, 's, (&'static CollisionBox, &'static Transform, &'static Solid)>,
+    hazards: Query<'w, 's, (&'static CollisionBox, &'static Transform, &'static Hazard)>,
+    actuators: Query<'w, 's, (&'static CollisionBox, &'static Transform, &'static Actuator)>,
+}
+
+impl<'w, 's> Navigation<'w, 's> {
+    pub fn grid(&self) -> Option<NavigationGrid> {
+        let terrain = self.terrain.get_single().ok()?;
+        Some(
+            NavigationGrid::new(terrain, self.solids.iter())
+                .with_hazards(self.hazards.iter())
+                .with_decks(self.actuators.iter()),
+        )
+    }
+
+    pub fn find_path(&self, from: Vec2, to: Vec2, character: &Character) -> Option<Vec<Vec2>> {
+        self.grid()?.find_path(from, to, character)
+    }
+}
+
+#[derive(Component)]
+pub struct NavigationTerrain {
+    pub width: usize,
+    pub height: usize,
+    pub walkable: Vec<bool>,
+}
+
+impl NavigationTerrain {
+    pub fn from_ground(ground: &Tilemap) -> NavigationTerrain {
+        let mut walkable = Vec::new();
+        for y in 0..ground.height() {
+            for x in 0..ground.width() {
+                walkable.push(ground.has_tile(x, y));
+            }
+        }
+        NavigationTerrain {
+            width: ground.width(),
+            height: ground.height(),
+            walkable,
+        }
+    }
+}
+
+pub type Tile = (isize, isize);
+
+pub struct NavigationGrid {
+    width: usize,
+    height: usize,
+    walkable: Vec<bool>,
+    // whitelists of all solids overlapping a cell, `None` blocks everyone
+    blockers: Vec<Vec<Option<Character>>>,
+    hazards: Vec<Vec<HazardKind>>,
+}
+
+impl NavigationGrid {
+    pub fn new<'a, I>(terrain: &NavigationTerrain, solids: I) -> NavigationGrid
+    where
+        I: Iterator<Item = (&'a CollisionBox, &'a Transform, &'a Solid)>,
+    {
+        let mut blockers = vec![Vec::new(); terrain.width * terrain.height];
+        for (collision_box, transform, solid) in solids {
+            let solid_collider =
+                collision_box.to_collider(transform.translation.x, transform.translation.y);
+            for y in 0..terrain.height {
+                for x in 0..terrain.width {
+                    let center = tile_center((x as isize, y as isize));
+                    if Collider::Circle(center, CELL_CLEARANCE).does_collide(&solid_collider) {
+                        blockers[x + y * terrain.width].push(solid.whitelisted.clone());
+                    }
+                }
+            }
+        }
+        NavigationGrid {
+            width: terrain.width,
+            height: terrain.height,
+            walkable: terrain.walkable.clone(),
+            blockers,
+            hazards: vec![Vec::new(); terrain.width * terrain.height],
+        }
+    }
+
+    pub fn with_hazards<'a, I>(mut self, hazards: I) -> NavigationGrid
+    where
+        I: Iterator<Item = (&'a CollisionBox, &'a Transform, &'a Hazard)>,
+    {
+        for (collision_box, transform, hazard) in hazards {
+            let hazard_collider =
+                collision_box.to_collider(transform.translation.x, transform.translation.y);
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let center = tile_center((x as isize, y as isize));
+                    if Collider::Circle(center, CELL_CLEARANCE).does_collide(&hazard_collider) {
+                        self.hazards[x + y * self.width].push(hazard.kind);
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    // cells below a deck are walkable and safe, the deck covers the ground or hazard there
+    pub fn with_decks<'a, I>(mut self, actuators: I) -> NavigationGrid
+    where
+        I: Iterator<Item = (&'a CollisionBox, &'a Transform, &'a Actuator)>,
+    {
+        for (collision_box, transform, _) in actuators.filter(|(_, _, actuator)| actuator.is_deck())
+        {
+            let deck_collider =
+                collision_box.to_collider(transform.translation.x, transform.translation.y);
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let center = tile_center((x as isize, y as isize));
+                    if Collider::Circle(center, 0.0).does_collide(&deck_collider) {
+                        self.walkable[x + y * self.width] = true;
+                        self.hazards[x + y * self.width].clear();
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    pub fn is_walkable(&self, tile: Tile, character: &Character) -> bool {
+        let (x, y) = tile;
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return false;
+        }
+        let index = x as usize + y as usize * self.width;
+        self.walkable[index]
+            && self.blockers[index]
+                .iter()
+                .all(|whitelisted| whitelisted.as_ref() == Some(character))
+            && !self.hazards[index]
+                .iter()
+                .any(|hazard| hazard.is_deadly_for(character))
+    }
+
+    pub fn find_path(&self, from: Vec2, to: Vec2, character: &Character) -> Option<Vec<Vec2>> {
+        let start = tile_of(from);
+        let goal = tile_of(to);
+        if !self.is_walkable(goal, character) {
+            return None;
+        }
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<Tile, Tile> = HashMap::default();
+        let mut costs: HashMap<Tile, u32> = HashMap::default();
+        open.push(Reverse((octile_distance(start, goal), start)));
+        costs.insert(start, 0);
+        while let Some(Reverse((_, tile))) = open.pop() {
+            if tile == goal {
+                let mut path = vec![to];
+                let mut current = tile;
+                while let Some(previous) = came_from.get(&current) {
+                    if *previous != start {
+                        path.push(tile_center(*previous));
+                    }
+                    current = *previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let cost = costs[&tile];
+            for (neighbour, step_cost) in self.neighbours(tile, character) {
+                let neighbour_cost = cost + step_cost;
+                if costs
+                    .get(&neighbour)
+                    .map_or(true, |known| neighbour_cost < *known)
+                {
+                    costs.insert(neighbour, neighbour_cost);
+                    came_from.insert(neighbour, tile);
+                    open.push(Reverse((
+                        neighbour_cost + octile_distance(neighbour, goal),
+                        neighbour,
+                    )));
+                }
+            }
+        }
+        None
+    }
+
+    fn neighbours(&self, (x, y): Tile, character: &Character) -> Vec<(Tile, u32)> {
+        let mut neighbours = Vec::new();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if dx == 0 && dy == 0 || !self.is_walkable((x + dx, y + dy), character) {
+                    continue;
+                }
+                if dx != 0 && dy != 0 {
+                    // don't cut corners of solids
+                    if !self.is_walkable((x + dx, y), character)
+                        || !self.is_walkable((x, y + dy), character)
+                    {
+                        continue;
+                    }
+                    neighbours.push(((x + dx, y + dy), DIAGONAL_COST));
+                } else {
+                    neighbours.push(((x + dx, y + dy), STRAIGHT_COST));
+                }
+            }
+        }
+        neighbours
+    }
+}
+
+pub fn tile_of(position: Vec2) -> Tile {
+    (
+        (position.x / TILE_SIZE).round() as isize,
+        (-position.y / TILE_SIZE).round() as isize,
+    )
+}
+
+pub fn tile_center((x, y): Tile) -> Vec2 {
+    Vec2::new(x as f32 * TILE_SIZE, -(y as f32) * TILE_SIZE)
+}
+
+fn octile_distance((ax, ay): Tile, (bx, by): Tile) -> u32 {
+    let dx = (ax - bx).unsigned_abs() as u32;
+    let dy = (ay - by).unsigned_abs() as u32;
+    STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+}
+
+pub fn walk_paths(
+    time: Res<FixedTime>,
+    mut characters: Query<(
+        &Character,
+        &Transform,
+        &mut Walking,
+        &mut NavigationPath,
+        &mut Movement,
+    )>,
+) {
+    for (character, transform, mut walking, mut path, mut movement) in &mut characters {
+        if path.waypoints.is_empty() {
+            path.last_step = None;
+            continue;
+        }
+        let position = transform.translation.truncate();
+        if let Some((last_position, last_step)) = path.last_step.take() {
+            if position.distance(last_position) < last_step * MIN_PROGRESS {
+                info!("{:?} is stuck, dropping its path", character);
+                path.waypoints.clear();
+                walking.walking = false;
+                continue;
+            }
+        }
+        while path
+            .waypoints
+            .first()
+            .is_some_and(|waypoint| waypoint.distance(position) < WAYPOINT_DISTANCE)
+        {
+            path.waypoints.remove(0);
+        }
+        let Some(waypoint) = path.waypoints.first().copied() else {
+            walking.walking = false;
+            continue;
+        };
+        walking.walking = true;
+        let delta_seconds = time.period.as_secs_f32();
+        let step = (character.speed() * delta_seconds).min(position.distance(waypoint));
+        if delta_seconds > 0.0 {
+            movement.intent = (waypoint - position).normalize_or_zero() * step / delta_seconds;
+        }
+        path.last_step = Some((position, step));
+    }
+}
@@ -0,0 +1,320 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::GameState;
+
+use super::{
+    character::CurrentCharacter,
+    goal::LevelGoal,
+    level::Collectible,
+    level_mgr::{LevelManager, LoadedLevel, ManagedLevel},
+    speedrun::GhostTrack,
+};
+
+const PROGRESS_FILE: &str = "progress.json";
+
+#[derive(Component)]
+pub struct LevelStats {
+    pub level: ManagedLevel,
+    pub par_seconds: f32,
+    pub max_switches: usize,
+    pub elapsed_seconds: f32,
+    pub switches: usize,
+    // set by cheats in the developer console, such runs don't become the ghost
+    pub used_console: bool,
+}
+
+impl LevelStats {
+    pub fn new(level: ManagedLevel, par_seconds: f32, max_switches: usize) -> LevelStats {
+        LevelStats {
+            level,
+            par_seconds,
+            max_switches,
+            elapsed_seconds: 0.0,
+            switches: 0,
+            used_console: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StarRating {
+    pub all_collected: bool,
+    pub par_time: bool,
+    pub few_switches: bool,
+}
+
+impl StarRating {
+    pub fn stars(&self) -> [bool; 3] {
+        [self.all_collected, self.par_time, self.few_switches]
+    }
+
+    pub fn count(&self) -> usize {
+        self.stars().iter().filter(|star| **star).count()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LevelRecord {
+    pub stars: [bool; 3],
+    pub best_seconds: Option<f32>,
+    pub ghost: Option<GhostTrack>,
+}
+
+// best results per level, stars once earned are kept
+#[derive(Resource, Default)]
+pub struct Progress {
+    pub records: HashMap<String, LevelRecord>,
+    // where the records are saved, they are only kept in memory without one
+    pub path: Option<PathBuf>,
+}
+
+impl Progress {
+    fn load(path: PathBuf) -> Progress {
+        let Ok(file) = fs::File::open(&path) else {
+            return Progress {
+                path: Some(path),
+                ..default()
+            };
+        };
+        let Ok(progress) = serde_json::from_reader::<_, serde_json::Value>(file) else {
+            warn!(
+                "Could not parse {}, starting without progress",
+                path.display()
+            );
+            return Progress {
+                path: Some(path),
+                ..default()
+            };
+        };
+        let records = progress
+            .as_object()
+            .map(|levels| {
+                levels
+                    .iter()
+                    .map(|(level, record)| {
+                        let mut stars = [false; 3];
+                        for (i, star) in stars.iter_mut().enumerate() {
+                            *star = record["stars"][i].as_bool().unwrap_or(false);
+                        }
+                        let best_seconds = record["best_seconds"].as_f64().map(|s| s as f32);
+                        (
+                            level.clone(),
+                            LevelRecord {
+                                stars,
+                                best_seconds,
+                                ghost: GhostTrack::from_json(&record["ghost"]),
+                            },
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Progress {
+            records,
+            path: Some(path),
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let progress: serde_json::Map<String, serde_json::Value> = self
+            .records
+            .iter()
+            .map(|(level, record)| {
+                (
+                    level.clone(),
+                    serde_json::json!({
+                        "stars": record.stars,
+                        "best_seconds": record.best_seconds,
+                        "ghost": record.ghost.as_ref().map(GhostTrack::to_json),
+                    }),
+                )
+            })
+            .collect();
+        if let Err(err) = fs::write(path, serde_json::Value::Object(progress).to_string()) {
+            warn!("Could not save progress: {}", err);
+        }
+    }
+
+    pub fn get(&self, level: ManagedLevel) -> Option<&LevelRecord> {
+        self.records.get(&format!("{:?}", level))
+    }
+
+    // only the fastest run of a level is kept as its ghost
+    pub fn record_ghost(&mut self, level: ManagedLevel, ghost: GhostTrack) {
+        let record = self.records.entry(format!("{:?}", level)).or_default();
+        if record
+            .ghost
+            .as_ref()
+            .is_some_and(|best| best.seconds <= ghost.seconds)
+        {
+            return;
+        }
+        record.ghost = Some(ghost);
+        self.save();
+    }
+
+    fn record(&mut self, level: ManagedLevel, rating: &StarRating, seconds: f32) {
+        let record = self.records.entry(format!("{:?}", level)).or_default();
+        for (saved, earned) in record.stars.iter_mut().zip(rating.stars()) {
+            *saved |= earned;
+        }
+        record.best_seconds = Some(
+            record
+                .best_seconds
+                .map_or(seconds, |best| best.min(seconds)),
+        );
+        self.save();
+    }
+}
+
+#[derive(Component)]
+struct LevelCompleteScreen;
+
+pub struct RatingPlugin;
+
+impl Plugin for RatingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Progress::load(PathBuf::from(PROGRESS_FILE)));
+        app.add_systems(
+            FixedUpdate,
+            count_level_stats.run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            (show_level_complete, continue_to_next_level)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn count_level_stats(
+    time: Res<FixedTime>,
+    goal: Query<&LevelGoal>,
+    player_query: Query<Ref<CurrentCharacter>>,
+    mut stats: Query<&mut LevelStats>,
+) {
+    let (Ok(goal), Ok(mut stats)) = (goal.get_single(), stats.get_single_mut()) else {
+        return;
+    };
+    if goal.completed {
+        return;
+    }
+    stats.elapsed_seconds += time.period.as_secs_f32();
+    if player_query
+        .get_single()
+        .is_ok_and(|current| current.is_changed() && !current.is_added())
+    {
+        stats.switches += 1;
+    }
+}
+
+fn show_level_complete(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut progress: ResMut<Progress>,
+    goal: Query<&LevelGoal, Changed<LevelGoal>>,
+    stats: Query<&LevelStats>,
+    collectibles: Query<&Collectible>,
+    screens: Query<(), With<LevelCompleteScreen>>,
+) {
+    let (Ok(goal), Ok(stats)) = (goal.get_single(), stats.get_single()) else {
+        return;
+    };
+    if !goal.completed || !screens.is_empty() {
+        return;
+    }
+    let rating = StarRating {
+        // a level without paw tokens can't award the token star
+        all_collected: !collectibles.is_empty() && collectibles.iter().all(|item| item.collected),
+        par_time: stats.elapsed_seconds <= stats.par_seconds,
+        few_switches: stats.switches <= stats.max_switches,
+    };
+    info!("{:?} completed with {} stars", stats.level, rating.count());
+    progress.record(stats.level, &rating, stats.elapsed_seconds);
+
+    let font: Handle<Font> = asset_server.load("fonts/NotoSans-Regular.ttf");
+    let title_style = TextStyle {
+        font: font.clone(),
+        font_size: 48.0,
+        color: Color::WHITE,
+    };
+    let line_style = TextStyle {
+        font,
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+    let star = |earned: bool| if earned { "[*]" } else { "[ ]" };
+    let collected = collectibles.iter().filter(|item| item.collected).count();
+    let lines = [
+        format!(
+            "{} Paw tokens {}/{}",
+            star(rating.all_collected),
+            collected,
+            collectibles.iter().count()
+        ),
+        format!(
+            "{} Time {:.1}s (par {:.0}s)",
+            star(rating.par_time),
+            stats.elapsed_seconds,
+            stats.par_seconds
+        ),
+        format!(
+            "{} Switches {} (max {})",
+            star(rating.few_switches),
+            stats.switches,
+            stats.max_switches
+        ),
+        if goal.next_level.is_some() {
+            "Press Enter to continue".to_string()
+        } else {
+            "Thanks for playing!".to_string()
+        },
+    ];
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+                ..default()
+            },
+            LevelCompleteScreen,
+            LoadedLevel { level: stats.level },
+        ))
+        .with_children(|p| {
+            p.spawn(TextBundle::from_section(
+                format!("Level complete! {}/3", rating.count()),
+                title_style,
+            ));
+            for line in lines {
+                p.spawn(TextBundle::from_section(line, line_style.clone()));
+            }
+        });
+}
+
+fn continue_to_next_level(
+    keys: Res<Input<KeyCode>>,
+    goal: Query<&LevelGoal>,
+    screens: Query<(), With<LevelCompleteScreen>>,
+    mut manager: Query<&mut LevelManager>,
+) {
+    if screens.is_empty() || !keys.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        return;
+    }
+    if let Some(next) = goal.get_single().ok().and_then(|goal| goal.next_level) {
+        manager.single_mut().next = Some(next);
+    }
+}
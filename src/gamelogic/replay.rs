@@ -0,0 +1,366 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    actions::{begin_tick, ActionSet, Actions, PlayerActions},
+    follow::FollowMode,
+    level_mgr::{LevelManager, ManagedLevel},
+};
+
+const REPLAY_DIR: &str = "replays";
+
+// the input of every tick since the level was loaded
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub level: ManagedLevel,
+    pub ticks: Vec<PlayerActions>,
+}
+
+fn flag(set: bool, letter: char) -> Option<char> {
+    set.then_some(letter)
+}
+
+fn action_line(actions: &PlayerActions) -> String {
+    let flags: String = [
+        flag(actions.toggle_camera, 'c'),
+        flag(actions.interact, 'e'),
+        flag(actions.pass_item, 'q'),
+        flag(actions.toggle_follow, 'g'),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    format!(
+        "{} {} {} {} {}",
+        actions.movement.x,
+        actions.movement.y,
+        actions
+            .switch_to
+            .map_or("-".to_string(), |index| index.to_string()),
+        if flags.is_empty() {
+            "-"
+        } else {
+            flags.as_str()
+        },
+        actions.walk_to.map_or("-".to_string(), |target| format!(
+            "{},{}",
+            target.x, target.y
+        ))
+    )
+}
+
+fn parse_action_line(line: &str) -> Option<PlayerActions> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let [x, y, switch_to, flags, walk_to] = words.as_slice() else {
+        return None;
+    };
+    if *flags != "-" && !flags.chars().all(|letter| "ceqg".contains(letter)) {
+        return None;
+    }
+    let walk_to = match *walk_to {
+        "-" => None,
+        target => {
+            let (x, y) = target.split_once(',')?;
+            Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+        }
+    };
+    Some(PlayerActions {
+        movement: Vec2::new(x.parse().ok()?, y.parse().ok()?),
+        switch_to: match *switch_to {
+            "-" => None,
+            index => Some(index.parse().ok()?),
+        },
+        toggle_camera: flags.contains('c'),
+        interact: flags.contains('e'),
+        pass_item: flags.contains('q'),
+        toggle_follow: flags.contains('g'),
+        walk_to,
+    })
+}
+
+impl Replay {
+    pub fn new(level: ManagedLevel) -> Replay {
+        Replay {
+            level,
+            ticks: Vec::new(),
+        }
+    }
+
+    pub fn path(level: ManagedLevel) -> PathBuf {
+        Path::new(REPLAY_DIR).join(format!("{:?}.replay", level).to_lowercase())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("level {:?}\n", self.level);
+        for actions in &self.ticks {
+            text.push_str(&action_line(actions));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Replay, String> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let level = header
+            .strip_prefix("level ")
+            .and_then(|name| ManagedLevel::levels().find(|level| format!("{:?}", level) == name))
+            .ok_or(format!("Unknown level in replay header: {}", header))?;
+        let ticks = lines
+            .enumerate()
+            .map(|(i, line)| {
+                parse_action_line(line).ok_or(format!("Invalid replay line {}: {}", i + 2, line))
+            })
+            .collect::<Result<Vec<PlayerActions>, String>>()?;
+        Ok(Replay { level, ticks })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, String> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Replay::from_text(&text)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|err| err.to_string())?;
+        }
+        fs::write(path, self.to_text()).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+#[derive(Default)]
+pub enum ReplayMode {
+    #[default]
+    Idle,
+    // waiting for the level to be reloaded
+    StartRecording,
+    Recording(Replay),
+    StartPlayback(Replay),
+    Playback {
+        replay: Replay,
+        tick: usize,
+    },
+}
+
+#[derive(Resource, Default)]
+pub struct ReplayState {
+    pub mode: ReplayMode,
+}
+
+impl ReplayState {
+    pub fn play(&mut self, replay: Replay, manager: &mut LevelManager) {
+        manager.next = Some(replay.level);
+        self.mode = ReplayMode::StartPlayback(replay);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(
+            self.mode,
+            ReplayMode::StartPlayback(_) | ReplayMode::Playback { .. }
+        )
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayState>();
+        app.add_systems(Update, replay_controls.run_if(in_state(GameState::InGame)));
+        app.add_systems(
+            FixedUpdate,
+            run_replay
+                .in_set(ActionSet)
+                .after(begin_tick)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(OnExit(GameState::InGame), stop_replay);
+    }
+}
+
+fn save_recording(replay: &Replay) {
+    let path = Replay::path(replay.level);
+    match replay.save(&path) {
+        Ok(()) => info!(
+            "Saved {} ticks of {:?} to {}",
+            replay.ticks.len(),
+            replay.level,
+            path.display()
+        ),
+        Err(err) => error!("Could not save the replay: {}", err),
+    }
+}
+
+fn replay_controls(
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<ReplayState>,
+    mut manager: Query<&mut LevelManager>,
+) {
+    let Ok(mut manager) = manager.get_single_mut() else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::F9) {
+        match std::mem::take(&mut state.mode) {
+            ReplayMode::Recording(replay) => save_recording(&replay),
+            ReplayMode::Idle => {
+                // recordings always start from a freshly loaded level
+                manager.next = manager.current;
+                state.mode = ReplayMode::StartRecording;
+                info!("Recording");
+            }
+            mode => state.mode = mode,
+        }
+    }
+    if keys.just_pressed(KeyCode::F10) {
+        if state.is_playing() {
+            state.mode = ReplayMode::Idle;
+            info!("Stopped the replay");
+        } else if let Some(level) = manager.current {
+            match Replay::load(Replay::path(level)) {
+                Ok(replay) => {
+                    if let ReplayMode::Recording(recording) = std::mem::take(&mut state.mode) {
+                        save_recording(&recording);
+                    }
+                    state.play(replay, &mut manager);
+                    info!("Replaying {:?}", level);
+                }
+                Err(err) => info!("No replay to play: {}", err),
+            }
+        }
+    }
+}
+
+// runs after the actions of the tick are known, so recordings see exactly what the game sees
+fn run_replay(
+    mut state: ResMut<ReplayState>,
+    mut actions: ResMut<Actions>,
+    mut follow_mode: ResMut<FollowMode>,
+    manager: Query<&LevelManager>,
+) {
+    let Ok(manager) = manager.get_single() else {
+        return;
+    };
+    // ticks before the reload finished don't belong to the new level
+    let loading = manager.next.is_some();
+    state.mode = match std::mem::take(&mut state.mode) {
+        ReplayMode::StartRecording if !loading => match manager.current {
+            Some(level) => {
+                follow_mode.reset();
+                let mut replay = Replay::new(level);
+                replay.ticks.push(actions.current.clone());
+                ReplayMode::Recording(replay)
+            }
+            None => ReplayMode::Idle,
+        },
+        ReplayMode::Recording(replay) if loading || manager.current != Some(replay.level) => {
+            save_recording(&replay);
+            ReplayMode::Idle
+        }
+        ReplayMode::Recording(mut replay) => {
+            replay.ticks.push(actions.current.clone());
+            ReplayMode::Recording(replay)
+        }
+        ReplayMode::StartPlayback(replay) if !loading => {
+            follow_mode.reset();
+            actions.pending = PlayerActions::default();
+            advance_playback(replay, 0, &mut actions)
+        }
+        ReplayMode::Playback { replay, .. } if loading => {
+            info!("Replay of {:?} interrupted", replay.level);
+            ReplayMode::Idle
+        }
+        ReplayMode::Playback { replay, tick } => advance_playback(replay, tick, &mut actions),
+        mode => mode,
+    };
+}
+
+fn advance_playback(replay: Replay, tick: usize, actions: &mut Actions) -> ReplayMode {
+    match replay.ticks.get(tick) {
+        Some(tick_actions) => {
+            actions.current = tick_actions.clone();
+            ReplayMode::Playback {
+                replay,
+                tick: tick + 1,
+            }
+        }
+        None => {
+            info!("Replay of {:?} finished", replay.level);
+            actions.current = PlayerActions::default();
+            ReplayMode::Idle
+        }
+    }
+}
+
+fn stop_replay(mut state: ResMut<ReplayState>) {
+    if let ReplayMode::Recording(replay) = std::mem::take(&mut state.mode) {
+        save_recording(&replay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_round_trip() {
+        let mut replay = Replay::new(ManagedLevel::Level2);
+        replay.ticks = vec![
+            PlayerActions::default(),
+            PlayerActions {
+                movement: Vec2::new(-0.70710677, 0.70710677),
+                ..default()
+            },
+            PlayerActions {
+                switch_to: Some(1),
+                toggle_camera: true,
+                interact: true,
+                pass_item: true,
+                toggle_follow: true,
+                ..default()
+            },
+            PlayerActions {
+                walk_to: Some(Vec2::new(544.5, -608.25)),
+                ..default()
+            },
+        ];
+        let text = replay.to_text();
+        assert_eq!(Replay::from_text(&text), Ok(replay));
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        assert!(parse_action_line("0 1 - - -").is_some());
+        for line in [
+            "",
+            "0 1 - -",
+            "0 1 - - - -",
+            "left 1 - - -",
+            "0 1 first - -",
+            "0 1 -1 - -",
+            "0 1 - x -",
+            "0 1 - - 3",
+            "0 1 - - 3,up",
+        ] {
+            assert_eq!(parse_action_line(line), None, "accepted {:?}", line);
+        }
+    }
+
+    #[test]
+    fn unknown_levels_and_bad_lines_fail_to_load() {
+        assert!(Replay::from_text("level Level9\n").is_err());
+        assert!(Replay::from_text("0 0 - - -\n").is_err());
+        assert_eq!(
+            Replay::from_text("level Level1\n0 0 - - -\nbroken\n"),
+            Err("Invalid replay line 3: broken".to_string())
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::GameState;
+
+use super::{
+    character::{toggle_levers, trigger_push_buttons},
+    level::PushButton,
+};
+
+#[derive(Clone, Debug)]
+pub enum Gate {
+    And,
+    Or,
+    Xor,
+    // NOR over all inputs, so a single input is a plain NOT
+    Not,
+    // first input sets, second input resets
+    Latch,
+    Delay {
+        rise_seconds: f32,
+        fall_seconds: f32,
+    },
+}
+
+pub struct SignalNode {
+    pub gate: Gate,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    output: bool,
+    pending: f32,
+}
+
+impl SignalNode {
+    pub fn new(gate: Gate, inputs: Vec<String>, outputs: Vec<String>) -> SignalNode {
+        SignalNode {
+            gate,
+            inputs,
+            outputs,
+            output: false,
+            pending: 0.0,
+        }
+    }
+
+    fn evaluate(&mut self, channels: &HashMap<String, bool>, delta_seconds: f32) -> bool {
+        let inputs: Vec<bool> = self
+            .inputs
+            .iter()
+            .map(|input| channels.get(input).copied().unwrap_or(false))
+            .collect();
+        self.output = match self.gate {
+            Gate::And => inputs.iter().all(|input| *input),
+            Gate::Or => inputs.iter().any(|input| *input),
+            Gate::Xor => inputs.iter().filter(|input| **input).count() % 2 == 1,
+            Gate::Not => !inputs.iter().any(|input| *input),
+            Gate::Latch => match (inputs.first().copied(), inputs.get(1).copied()) {
+                (Some(true), _) => true,
+                (_, Some(true)) => false,
+                _ => self.output,
+            },
+            Gate::Delay {
+                rise_seconds,
+                fall_seconds,
+            } => {
+                let input = inputs.iter().any(|input| *input);
+                if input == self.output {
+                    self.pending = 0.0;
+                    self.output
+                } else {
+                    self.pending += delta_seconds;
+                    let delay = if input { rise_seconds } else { fall_seconds };
+                    if self.pending >= delay {
+                        self.pending = 0.0;
+                        input
+                    } else {
+                        self.output
+                    }
+                }
+            }
+        };
+        self.output
+    }
+}
+
+// Buttons drive and bridges listen to the channel named after their index,
+// other actuators and nodes may use any channel name.
+#[derive(Component, Default)]
+pub struct SignalNetwork {
+    pub channels: HashMap<String, bool>,
+    pub nodes: Vec<SignalNode>,
+}
+
+impl SignalNetwork {
+    pub fn get(&self, channel: &str) -> bool {
+        self.channels.get(channel).copied().unwrap_or(false)
+    }
+
+    // `buttons` are the index and pressed state of every button
+    pub fn update<I>(&mut self, buttons: I, delta_seconds: f32)
+    where
+        I: Iterator<Item = (usize, bool)>,
+    {
+        let mut channels: HashMap<String, bool> = HashMap::default();
+        for (index, pressed) in buttons {
+            *channels.entry(index_channel(index)).or_default() |= pressed;
+        }
+        // nodes are evaluated in level data order, so later nodes see the results of earlier ones
+        for node in self.nodes.iter_mut() {
+            let output = node.evaluate(&channels, delta_seconds);
+            for channel in &node.outputs {
+                *channels.entry(channel.clone()).or_default() |= output;
+            }
+        }
+        self.channels = channels;
+    }
+}
+
+pub fn index_channel(index: usize) -> String {
+    index.to_string()
+}
+
+pub struct SignalPlugin;
+
+impl Plugin for SignalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            update_signals
+                .after(trigger_push_buttons)
+                .after(toggle_levers)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+pub fn update_signals(
+    time: Res<FixedTime>,
+    buttons: Query<&PushButton>,
+    mut networks: Query<&mut SignalNetwork>,
+) {
+    for mut network in &mut networks {
+        network.update(
+            buttons.iter().map(|button| (button.index, button.pressed)),
+            time.period.as_secs_f32(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(gate: Gate, inputs: usize) -> SignalNetwork {
+        SignalNetwork {
+            nodes: vec![SignalNode::new(
+                gate,
+                (0..inputs).map(index_channel).collect(),
+                vec!["out".to_string()],
+            )],
+            ..default()
+        }
+    }
+
+    fn truth_table(gate: Gate) -> Vec<bool> {
+        let mut network = network(gate, 2);
+        [(false, false), (true, false), (false, true), (true, true)]
+            .into_iter()
+            .map(|(a, b)| {
+                network.update([(0, a), (1, b)].into_iter(), 0.1);
+                network.get("out")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn not_gate() {
+        let mut network = network(Gate::Not, 1);
+        network.update([(0, false)].into_iter(), 0.1);
+        assert!(network.get("out"));
+        network.update([(0, true)].into_iter(), 0.1);
+        assert!(!network.get("out"));
+        assert_eq!(truth_table(Gate::Not), [true, false, false, false]);
+    }
+
+    #[test]
+    fn and_gate() {
+        assert_eq!(truth_table(Gate::And), [false, false, false, true]);
+    }
+
+    #[test]
+    fn or_gate() {
+        assert_eq!(truth_table(Gate::Or), [false, true, true, true]);
+    }
+
+    #[test]
+    fn xor_gate() {
+        assert_eq!(truth_table(Gate::Xor), [false, true, true, false]);
+    }
+
+    #[test]
+    fn latch_gate() {
+        let mut network = network(Gate::Latch, 2);
+        let mut set = |a, b| {
+            network.update([(0, a), (1, b)].into_iter(), 0.1);
+            network.get("out")
+        };
+        assert!(!set(false, false));
+        assert!(set(true, false), "set");
+        assert!(set(false, false), "held after release");
+        assert!(!set(false, true), "reset");
+        assert!(!set(false, false), "stays reset");
+        assert!(set(true, true), "set wins over reset");
+    }
+
+    #[test]
+    fn delay_gate() {
+        let mut network = network(
+            Gate::Delay {
+                rise_seconds: 0.5,
+                fall_seconds: 1.0,
+            },
+            1,
+        );
+        let mut outputs = Vec::new();
+        for pressed in [true, true, true, false, false, false, false, false] {
+            network.update([(0, pressed)].into_iter(), 0.25);
+            outputs.push(network.get("out"));
+        }
+        assert_eq!(outputs, [false, true, true, true, true, true, false, false]);
+    }
+
+    #[test]
+    fn delay_restarts_when_the_input_flips_back() {
+        let mut network = network(
+            Gate::Delay {
+                rise_seconds: 0.5,
+                fall_seconds: 0.5,
+            },
+            1,
+        );
+        for pressed in [true, false, true] {
+            network.update([(0, pressed)].into_iter(), 0.25);
+            assert!(!network.get("out"));
+        }
+        network.update([(0, true)].into_iter(), 0.25);
+        assert!(network.get("out"));
+    }
+
+    #[test]
+    fn later_nodes_see_earlier_outputs() {
+        let mut network = SignalNetwork {
+            nodes: vec![
+                SignalNode::new(
+                    Gate::Not,
+                    vec![index_channel(0)],
+                    vec!["inverted".to_string()],
+                ),
+                SignalNode::new(
+                    Gate::And,
+                    vec!["inverted".to_string(), index_channel(1)],
+                    vec!["out".to_string()],
+                ),
+            ],
+            ..default()
+        };
+        network.update([(0, false), (1, true)].into_iter(), 0.1);
+        assert!(network.get("out"));
+        network.update([(0, true), (1, true)].into_iter(), 0.1);
+        assert!(!network.get("out"));
+    }
+}
@@ -0,0 +1,690 @@
+use std::{collections::VecDeque, path::Path};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    physics::{Collider, CollisionBox},
+    tilemap::{TileSet, Tilemap, Tiles},
+};
+
+use super::{
+    character::Character,
+    goal::WinCondition,
+    hazard::HAZARD_FOOTPRINT,
+    inventory::{Item, KeyColor},
+    level::{HazardKind, SwitchKind},
+    level_mgr::{LevelData, TILE_SIZE},
+    navigation::NavigationTerrain,
+    signal::{index_channel, SignalNetwork, SignalNode},
+};
+
+const ASSET_DIR: &str = "assets";
+// regions are sampled finer than tiles, some gaps only fit the smaller characters
+const SAMPLE_STEP: f32 = 4.0;
+// bodies can squeeze a little into solids before they are pushed back out
+const BODY_TOLERANCE: f32 = 3.0;
+// long enough for every delay node to have switched
+const SETTLE_SECONDS: f32 = 1000.0;
+const MAX_STATES: usize = 1_000_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Landmark {
+    Start(Character),
+    // indices are positions in the level data vecs
+    Button(usize),
+    Flag(Option<Character>),
+    Collectible(usize),
+    Item(usize),
+    Teleporter(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct Move {
+    pub character: Character,
+    pub landmark: Landmark,
+    pub position: Vec2,
+    // only filled in for the moves of a solution
+    pub path: Vec<Vec2>,
+}
+
+#[derive(Debug)]
+pub struct Solution {
+    pub moves: Vec<Move>,
+}
+
+struct Point {
+    landmark: Landmark,
+    position: Vec2,
+}
+
+enum Opening {
+    Never,
+    Channel { channel: String, negated: bool },
+    Key(KeyColor),
+}
+
+enum BlockerKind {
+    Solid { whitelisted: Option<Character> },
+    Hazard(HazardKind),
+}
+
+struct Blocker {
+    collider: Collider,
+    kind: BlockerKind,
+    opening: Opening,
+}
+
+// Characters only ever walk between points of interest, so a state is the point each
+// character stands on plus what was picked up and toggled on the way.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct State {
+    positions: Vec<usize>,
+    discovered: Vec<bool>,
+    collected: Vec<bool>,
+    levers: Vec<bool>,
+}
+
+// Not modelled: crates (pits stay closed), platforms, hazards below drawbridges, the
+// timing of timed plates and latches (both act like momentary buttons) and who carries a
+// key (any picked up key opens all gates of its colour).
+pub struct LevelModel<'a> {
+    data: &'a LevelData,
+    terrain: NavigationTerrain,
+    columns: usize,
+    rows: usize,
+    points: Vec<Point>,
+    blockers: Vec<Blocker>,
+    // connected walkable areas per character and open blockers, labelled per sample
+    regions: HashMap<(Character, Vec<bool>), Vec<Option<usize>>>,
+}
+
+impl<'a> LevelModel<'a> {
+    pub fn load(data: &'a LevelData) -> Option<LevelModel<'a>> {
+        let tile_set = TileSet::from_path(Path::new(ASSET_DIR).join(&data.tileset))?;
+        let ground = Tiles::from_path(Path::new(ASSET_DIR).join(data.tilemap_layers.first()?))?;
+        let terrain = NavigationTerrain::from_ground(&Tilemap::new(&tile_set, &ground)?);
+        Some(LevelModel::new(data, terrain))
+    }
+
+    pub fn new(data: &'a LevelData, terrain: NavigationTerrain) -> LevelModel<'a> {
+        let mut points: Vec<Point> = data
+            .characters
+            .iter()
+            .map(|character_data| Point {
+                landmark: Landmark::Start(character_data.character.clone()),
+                position: character_data.starting_position,
+            })
+            .collect();
+        points.extend(data.buttons.iter().enumerate().map(|(i, button)| Point {
+            landmark: Landmark::Button(i),
+            position: button.position,
+        }));
+        points.push(Point {
+            landmark: Landmark::Flag(None),
+            position: data.flag_position,
+        });
+        points.extend(data.character_flags.iter().map(|flag| Point {
+            landmark: Landmark::Flag(Some(flag.character.clone())),
+            position: flag.position,
+        }));
+        points.extend(
+            data.collectibles
+                .iter()
+                .enumerate()
+                .map(|(i, collectible)| Point {
+                    landmark: Landmark::Collectible(i),
+                    position: collectible.position,
+                }),
+        );
+        points.extend(data.items.iter().enumerate().map(|(i, item)| Point {
+            landmark: Landmark::Item(i),
+            position: item.position,
+        }));
+        points.extend(
+            data.teleporters
+                .iter()
+                .enumerate()
+                .map(|(i, teleporter)| Point {
+                    landmark: Landmark::Teleporter(i),
+                    position: teleporter.position,
+                }),
+        );
+
+        let rect = |corner_position: Vec2, size: Vec2| {
+            Collider::AABB(corner_position + (size - TILE_SIZE) / 2.0, size)
+        };
+        let tile = |position: Vec2| Collider::AABB(position, Vec2::splat(TILE_SIZE));
+        let mut blockers: Vec<Blocker> = Vec::new();
+        blockers.extend(
+            data.map_colliders_around_drawbridges()
+                .iter()
+                .map(|collider| Blocker {
+                    collider: rect(collider.corner_position, collider.size),
+                    kind: BlockerKind::Solid {
+                        whitelisted: collider.whitelisted.clone(),
+                    },
+                    opening: Opening::Never,
+                }),
+        );
+        blockers.extend(data.bridges.iter().map(|bridge| Blocker {
+            collider: Collider::AABB(bridge.position, Vec2::splat(16.0)),
+            kind: BlockerKind::Solid { whitelisted: None },
+            opening: Opening::Channel {
+                channel: index_channel(bridge.index),
+                negated: bridge.negated,
+            },
+        }));
+        blockers.extend(
+            data.actuators
+                .iter()
+                .filter(|actuator| actuator.kind.is_blocking())
+                .map(|actuator| Blocker {
+                    collider: tile(actuator.position),
+                    kind: BlockerKind::Solid { whitelisted: None },
+                    opening: Opening::Channel {
+                        channel: actuator.channel.clone(),
+                        negated: actuator.negated,
+                    },
+                }),
+        );
+        blockers.extend(data.pits.iter().map(|pit| Blocker {
+            collider: tile(pit.position),
+            kind: BlockerKind::Solid { whitelisted: None },
+            opening: Opening::Never,
+        }));
+        blockers.extend(data.locked_gates.iter().map(|gate| Blocker {
+            collider: tile(gate.position),
+            kind: BlockerKind::Solid { whitelisted: None },
+            opening: Opening::Key(gate.key),
+        }));
+        blockers.extend(data.hazards.iter().map(|hazard| Blocker {
+            collider: rect(hazard.corner_position, hazard.size),
+            kind: BlockerKind::Hazard(hazard.kind),
+            opening: Opening::Never,
+        }));
+
+        LevelModel {
+            columns: (terrain.width as f32 * TILE_SIZE / SAMPLE_STEP) as usize,
+            rows: (terrain.height as f32 * TILE_SIZE / SAMPLE_STEP) as usize,
+            data,
+            terrain,
+            points,
+            blockers,
+            regions: HashMap::default(),
+        }
+    }
+
+    // breadth first, so the solution has the fewest moves
+    pub fn solve(&mut self) -> Option<Solution> {
+        let start = State {
+            positions: (0..self.data.characters.len()).collect(),
+            discovered: self
+                .data
+                .characters
+                .iter()
+                .map(|character| character.is_discovered)
+                .collect(),
+            collected: vec![false; self.points.len()],
+            levers: vec![false; self.data.buttons.len()],
+        };
+        let mut came_from: HashMap<State, Option<(State, Move)>> = HashMap::default();
+        came_from.insert(start.clone(), None);
+        let mut open = VecDeque::from([start]);
+        while let Some(state) = open.pop_front() {
+            if self.is_won(&state) {
+                let mut moves = Vec::new();
+                let mut current = &state;
+                while let Some(Some((previous, step))) = came_from.get(current) {
+                    let mut step = step.clone();
+                    step.path = self.walk(previous, &step);
+                    moves.push(step);
+                    current = previous;
+                }
+                moves.reverse();
+                return Some(Solution { moves });
+            }
+            for (next, step) in self.successors(&state) {
+                if !came_from.contains_key(&next) {
+                    came_from.insert(next.clone(), Some((state.clone(), step)));
+                    open.push_back(next);
+                }
+            }
+            if came_from.len() > MAX_STATES {
+                warn!("Giving up after {} states", came_from.len());
+                return None;
+            }
+        }
+        None
+    }
+
+    fn successors(&mut self, state: &State) -> Vec<(State, Move)> {
+        let mut successors = Vec::new();
+        for mover in 0..state.positions.len() {
+            if !state.discovered[mover] {
+                continue;
+            }
+            let character = self.data.characters[mover].character.clone();
+            // buttons below the moving character are released as soon as it leaves
+            let network = self.signals(state, mover);
+            let open = self.open_blockers(&network, state);
+            let key = (character.clone(), open);
+            if !self.regions.contains_key(&key) {
+                let regions = self.label_regions(&key.0, &key.1);
+                self.regions.insert(key.clone(), regions);
+            }
+            let regions = &self.regions[&key];
+            let region_of = |position: Vec2| self.sample_of(position).and_then(|s| regions[s]);
+            let from = state.positions[mover];
+            let Some(region) = region_of(self.points[from].position) else {
+                continue;
+            };
+            for (target, point) in self.points.iter().enumerate() {
+                if target == from || region_of(point.position) != Some(region) {
+                    continue;
+                }
+                let mut next = state.clone();
+                self.arrive(&mut next, mover, target, &network);
+                successors.push((
+                    next,
+                    Move {
+                        character: character.clone(),
+                        landmark: point.landmark.clone(),
+                        position: point.position,
+                        path: Vec::new(),
+                    },
+                ));
+            }
+        }
+        successors
+    }
+
+    // the shortest way along the samples, the same region the move was found in
+    fn walk(&mut self, state: &State, step: &Move) -> Vec<Vec2> {
+        let Some(mover) = self
+            .data
+            .characters
+            .iter()
+            .position(|character_data| character_data.character == step.character)
+        else {
+            return Vec::new();
+        };
+        let network = self.signals(state, mover);
+        let open = self.open_blockers(&network, state);
+        let key = (step.character.clone(), open);
+        if !self.regions.contains_key(&key) {
+            let regions = self.label_regions(&key.0, &key.1);
+            self.regions.insert(key.clone(), regions);
+        }
+        let regions = &self.regions[&key];
+        let (Some(start), Some(goal)) = (
+            self.sample_of(self.points[state.positions[mover]].position),
+            self.sample_of(step.position),
+        ) else {
+            return Vec::new();
+        };
+        let mut came_from: HashMap<usize, usize> = HashMap::default();
+        came_from.insert(start, start);
+        let mut open_samples = VecDeque::from([start]);
+        while let Some(sample) = open_samples.pop_front() {
+            if sample == goal {
+                break;
+            }
+            for neighbour in self.sample_neighbours(sample) {
+                if regions[neighbour].is_some()
+                    && regions[neighbour] == regions[start]
+                    && !came_from.contains_key(&neighbour)
+                {
+                    came_from.insert(neighbour, sample);
+                    open_samples.push_back(neighbour);
+                }
+            }
+        }
+        if !came_from.contains_key(&goal) {
+            return Vec::new();
+        }
+        let mut path = vec![self.sample_position(goal)];
+        let mut sample = goal;
+        while sample != start {
+            sample = came_from[&sample];
+            path.push(self.sample_position(sample));
+        }
+        path.reverse();
+        path
+    }
+
+    fn arrive(&self, state: &mut State, mover: usize, target: usize, network: &SignalNetwork) {
+        let mut target = target;
+        match self.points[target].landmark {
+            Landmark::Button(i) if self.data.buttons[i].kind == SwitchKind::Lever => {
+                state.levers[i] = !state.levers[i];
+            }
+            Landmark::Collectible(_) | Landmark::Item(_) => state.collected[target] = true,
+            Landmark::Teleporter(i) => {
+                let pair = self.data.teleporters[i].pair;
+                let powered = |teleporter: usize| {
+                    self.data.teleporters[teleporter]
+                        .channel
+                        .as_ref()
+                        .map_or(true, |channel| network.get(channel))
+                };
+                let partner = self.points.iter().position(|point| {
+                    matches!(point.landmark, Landmark::Teleporter(j)
+                        if j != i && self.data.teleporters[j].pair == pair && powered(i) && powered(j))
+                });
+                if let Some(partner) = partner {
+                    target = partner;
+                }
+            }
+            _ => {}
+        }
+        state.positions[mover] = target;
+        for other in 0..state.positions.len() {
+            if state.positions[other] == target {
+                state.discovered[other] = true;
+            }
+        }
+    }
+
+    fn signals(&self, state: &State, mover: usize) -> SignalNetwork {
+        let buttons = self.data.buttons.iter().enumerate().map(|(i, button)| {
+            let on_button: Vec<&Character> = state
+                .positions
+                .iter()
+                .enumerate()
+                .filter(|(other, point)| {
+                    *other != mover && self.points[**point].landmark == Landmark::Button(i)
+                })
+                .map(|(other, _)| &self.data.characters[other].character)
+                .collect();
+            let pressed = match &button.kind {
+                SwitchKind::Momentary | SwitchKind::Timed { .. } => !on_button.is_empty(),
+                SwitchKind::Lever => state.levers[i],
+                SwitchKind::Character(required) => on_button.contains(&required),
+                SwitchKind::Weight { bodies } => on_button.len() >= *bodies,
+            };
+            (button.index, pressed)
+        });
+        let mut network = SignalNetwork {
+            nodes: self
+                .data
+                .signals
+                .iter()
+                .map(|node| {
+                    SignalNode::new(node.gate.clone(), node.inputs.clone(), node.outputs.clone())
+                })
+                .collect(),
+            ..default()
+        };
+        network.update(buttons, SETTLE_SECONDS);
+        network
+    }
+
+    fn open_blockers(&self, network: &SignalNetwork, state: &State) -> Vec<bool> {
+        let keys: Vec<KeyColor> = self
+            .points
+            .iter()
+            .zip(&state.collected)
+            .filter_map(|(point, collected)| match point.landmark {
+                Landmark::Item(i) if *collected => match self.data.items[i].item {
+                    Item::Key(key) => Some(key),
+                },
+                _ => None,
+            })
+            .collect();
+        self.blockers
+            .iter()
+            .map(|blocker| match &blocker.opening {
+                Opening::Never => false,
+                Opening::Channel { channel, negated } => network.get(channel) != *negated,
+                Opening::Key(key) => keys.contains(key),
+            })
+            .collect()
+    }
+
+    fn label_regions(&self, character: &Character, open: &[bool]) -> Vec<Option<usize>> {
+        let body_radius = match character.collision_box() {
+            CollisionBox::Circle { radius } => radius,
+            CollisionBox::AABB {
+                width_radius,
+                height_radius,
+            } => width_radius.max(height_radius) / 2.0,
+        } - BODY_TOLERANCE;
+        let blockers: Vec<(&Collider, f32)> = self
+            .blockers
+            .iter()
+            .zip(open)
+            .filter(|(_, open)| !**open)
+            .filter_map(|(blocker, _)| match &blocker.kind {
+                BlockerKind::Solid { whitelisted } if whitelisted.as_ref() != Some(character) => {
+                    Some((&blocker.collider, body_radius))
+                }
+                BlockerKind::Hazard(kind) if kind.is_deadly_for(character) => {
+                    Some((&blocker.collider, HAZARD_FOOTPRINT))
+                }
+                _ => None,
+            })
+            .collect();
+        let free: Vec<bool> = (0..self.columns * self.rows)
+            .map(|sample| {
+                let position = self.sample_position(sample);
+                self.is_ground(position)
+                    && blockers.iter().all(|(collider, radius)| {
+                        !Collider::Circle(position, *radius).does_collide(collider)
+                    })
+            })
+            .collect();
+        let mut labels = vec![None; free.len()];
+        let mut region = 0;
+        for start in 0..free.len() {
+            if !free[start] || labels[start].is_some() {
+                continue;
+            }
+            labels[start] = Some(region);
+            let mut open_samples = vec![start];
+            while let Some(sample) = open_samples.pop() {
+                for neighbour in self.sample_neighbours(sample) {
+                    if free[neighbour] && labels[neighbour].is_none() {
+                        labels[neighbour] = Some(region);
+                        open_samples.push(neighbour);
+                    }
+                }
+            }
+            region += 1;
+        }
+        labels
+    }
+
+    fn is_won(&self, state: &State) -> bool {
+        let on_flag = |character: &Character, owner: Option<&Character>| {
+            state.positions.iter().enumerate().any(|(i, point)| {
+                self.data.characters[i].character == *character
+                    && self.points[*point].landmark == Landmark::Flag(owner.cloned())
+            })
+        };
+        let discovered: Vec<&Character> = self
+            .data
+            .characters
+            .iter()
+            .zip(&state.discovered)
+            .filter(|(_, discovered)| **discovered)
+            .map(|(character_data, _)| &character_data.character)
+            .collect();
+        self.data
+            .win_conditions
+            .iter()
+            .all(|condition| match condition {
+                WinCondition::AllDiscoveredAtFlag => {
+                    discovered.iter().all(|character| on_flag(character, None))
+                }
+                WinCondition::CharactersAtFlag(required) => {
+                    required.iter().all(|character| on_flag(character, None))
+                }
+                WinCondition::AnyCharacterAtFlag => {
+                    discovered.iter().any(|character| on_flag(character, None))
+                }
+                WinCondition::CharactersAtOwnFlags => self
+                    .data
+                    .character_flags
+                    .iter()
+                    .all(|flag| on_flag(&flag.character, Some(&flag.character))),
+                WinCondition::AllCollected => {
+                    self.points
+                        .iter()
+                        .zip(&state.collected)
+                        .all(|(point, collected)| {
+                            !matches!(point.landmark, Landmark::Collectible(_)) || *collected
+                        })
+                }
+            })
+    }
+
+    fn is_ground(&self, position: Vec2) -> bool {
+        let x = (position.x / TILE_SIZE).round();
+        let y = (position.y / TILE_SIZE).round();
+        x >= 0.0
+            && y >= 0.0
+            && (x as usize) < self.terrain.width
+            && (y as usize) < self.terrain.height
+            && self.terrain.walkable[x as usize + y as usize * self.terrain.width]
+    }
+
+    fn sample_of(&self, position: Vec2) -> Option<usize> {
+        let x = (position.x / SAMPLE_STEP).round();
+        let y = (position.y / SAMPLE_STEP).round();
+        if x < 0.0 || y < 0.0 || x as usize >= self.columns || y as usize >= self.rows {
+            None
+        } else {
+            Some(x as usize + y as usize * self.columns)
+        }
+    }
+
+    fn sample_position(&self, sample: usize) -> Vec2 {
+        Vec2::new(
+            (sample % self.columns) as f32,
+            (sample / self.columns) as f32,
+        ) * SAMPLE_STEP
+    }
+
+    fn sample_neighbours(&self, sample: usize) -> Vec<usize> {
+        let (x, y) = (sample % self.columns, sample / self.columns);
+        let mut neighbours = Vec::new();
+        if x > 0 {
+            neighbours.push(sample - 1);
+        }
+        if x + 1 < self.columns {
+            neighbours.push(sample + 1);
+        }
+        if y > 0 {
+            neighbours.push(sample - self.columns);
+        }
+        if y + 1 < self.rows {
+            neighbours.push(sample + self.columns);
+        }
+        neighbours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+
+    use super::*;
+    use crate::gamelogic::{budget::MoveBudget, level_mgr::ManagedLevel};
+
+    #[test]
+    fn every_level_is_solvable() {
+        for level in ManagedLevel::levels() {
+            let mut model = LevelModel::load(level.get_data())
+                .unwrap_or_else(|| panic!("Could not load the tiles of {:?}", level));
+            assert!(model.solve().is_some(), "{:?} can't be solved", level);
+        }
+    }
+
+    #[test]
+    fn every_paw_token_can_be_collected() {
+        for level in ManagedLevel::levels() {
+            let mut data = level.get_data().clone();
+            assert!(
+                !data.collectibles.is_empty(),
+                "{:?} has no paw tokens",
+                level
+            );
+            data.win_conditions.push(WinCondition::AllCollected);
+            let mut model = LevelModel::load(&data)
+                .unwrap_or_else(|| panic!("Could not load the tiles of {:?}", level));
+            assert!(
+                model.solve().is_some(),
+                "{:?} can't be solved with all paw tokens",
+                level
+            );
+        }
+    }
+
+    // how much of a budget the solution uses when every move walks its path
+    fn cost(data: &LevelData, solution: &Solution, budget: &MoveBudget) -> f32 {
+        match budget {
+            MoveBudget::Distance(_) => solution
+                .moves
+                .iter()
+                .flat_map(|step| step.path.windows(2))
+                .map(|segment| segment[0].distance(segment[1]))
+                .sum(),
+            MoveBudget::Switches(_) => {
+                let mut current = &data.starting_character;
+                let mut switches = 0;
+                for step in &solution.moves {
+                    if step.character != *current {
+                        current = &step.character;
+                        switches += 1;
+                    }
+                }
+                switches as f32
+            }
+            MoveBudget::Tiles(_) => {
+                let tile = |position: &Vec2| {
+                    (
+                        (position.x / TILE_SIZE).round() as isize,
+                        (position.y / TILE_SIZE).round() as isize,
+                    )
+                };
+                let visited: HashSet<(isize, isize)> = data
+                    .characters
+                    .iter()
+                    .map(|character_data| tile(&character_data.starting_position))
+                    .chain(
+                        solution
+                            .moves
+                            .iter()
+                            .flat_map(|step| step.path.iter().map(tile)),
+                    )
+                    .collect();
+                visited.len() as f32
+            }
+        }
+    }
+
+    // the budgets are these costs plus some slack, nobody walks the sample grid exactly
+    #[test]
+    fn budgets_fit_the_shortest_solution() {
+        for level in ManagedLevel::levels() {
+            let data = level.get_data();
+            let Some(budget) = &data.budget else {
+                continue;
+            };
+            let mut model = LevelModel::load(data)
+                .unwrap_or_else(|| panic!("Could not load the tiles of {:?}", level));
+            let solution = model
+                .solve()
+                .unwrap_or_else(|| panic!("{:?} can't be solved", level));
+            let used = cost(data, &solution, budget);
+            assert!(
+                used <= budget.limit(),
+                "{:?} needs {} but the {} budget is {}",
+                level,
+                used,
+                budget.label(),
+                budget.limit()
+            );
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::GameState;
+
+use super::{
+    character::Character,
+    goal::{check_win_conditions, LevelGoal},
+    level_mgr::{LoadedLevel, ManagedLevel},
+    rating::{LevelStats, Progress},
+};
+
+// the ghost keeps one position every few ticks and moves in a straight line between them
+const SAMPLE_TICKS: usize = 6;
+const GHOST_ALPHA: f32 = 0.4;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GhostTrack {
+    pub seconds: f32,
+    pub positions: HashMap<Character, Vec<Vec2>>,
+}
+
+impl GhostTrack {
+    pub fn position(&self, character: &Character, tick: usize) -> Option<Vec2> {
+        let samples = self.positions.get(character)?;
+        let index = tick / SAMPLE_TICKS;
+        let Some(from) = samples.get(index) else {
+            return samples.last().copied();
+        };
+        let to = samples.get(index + 1).unwrap_or(from);
+        Some(from.lerp(*to, (tick % SAMPLE_TICKS) as f32 / SAMPLE_TICKS as f32))
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let positions: serde_json::Map<String, serde_json::Value> = self
+            .positions
+            .iter()
+            .map(|(character, samples)| {
+                let coordinates: Vec<f32> = samples
+                    .iter()
+                    .flat_map(|sample| [sample.x, sample.y])
+                    .collect();
+                (character.name().to_string(), serde_json::json!(coordinates))
+            })
+            .collect();
+        serde_json::json!({
+            "seconds": self.seconds,
+            "positions": positions,
+        })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Option<GhostTrack> {
+        let seconds = value["seconds"].as_f64()? as f32;
+        let positions = value["positions"]
+            .as_object()?
+            .iter()
+            .filter_map(|(name, coordinates)| {
+                let coordinates: Vec<f32> = coordinates
+                    .as_array()?
+                    .iter()
+                    .filter_map(|coordinate| coordinate.as_f64())
+                    .map(|coordinate| coordinate as f32)
+                    .collect();
+                let samples = coordinates
+                    .chunks_exact(2)
+                    .map(|sample| Vec2::new(sample[0], sample[1]))
+                    .collect();
+                Some((Character::from_name(name)?, samples))
+            })
+            .collect();
+        Some(GhostTrack { seconds, positions })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Split {
+    pub level: ManagedLevel,
+    pub seconds: f32,
+    // the best time of the level before this run
+    pub best: Option<f32>,
+}
+
+#[derive(Resource, Default)]
+pub struct Speedrun {
+    pub show_timer: bool,
+    pub show_ghost: bool,
+    // levels finished since the timer was switched on
+    pub splits: Vec<Split>,
+    tick: usize,
+    finished: bool,
+    // read when the level starts, the level complete screen replaces it on completion
+    best: Option<f32>,
+    recording: GhostTrack,
+}
+
+#[derive(Component)]
+struct Ghost {
+    character: Character,
+}
+
+#[derive(Component)]
+struct SpeedrunText;
+
+pub struct SpeedrunPlugin;
+
+impl Plugin for SpeedrunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Speedrun>();
+        app.add_systems(OnEnter(GameState::InGame), create_timer_text);
+        app.add_systems(OnExit(GameState::InGame), remove_timer_text);
+        app.add_systems(
+            Update,
+            (toggle_speedrun, spawn_ghosts, update_timer_text).run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (record_run.after(check_win_conditions), move_ghosts)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn toggle_speedrun(keys: Res<Input<KeyCode>>, mut speedrun: ResMut<Speedrun>) {
+    if keys.just_pressed(KeyCode::F7) {
+        speedrun.show_timer = !speedrun.show_timer;
+        speedrun.splits.clear();
+    }
+    if keys.just_pressed(KeyCode::F8) {
+        speedrun.show_ghost = !speedrun.show_ghost;
+    }
+}
+
+fn record_run(
+    mut speedrun: ResMut<Speedrun>,
+    mut progress: ResMut<Progress>,
+    goal: Query<&LevelGoal>,
+    stats: Query<Ref<LevelStats>>,
+    characters: Query<(&Character, &Transform)>,
+) {
+    let (Ok(goal), Ok(stats)) = (goal.get_single(), stats.get_single()) else {
+        return;
+    };
+    if stats.is_added() {
+        speedrun.tick = 0;
+        speedrun.finished = false;
+        speedrun.best = progress
+            .get(stats.level)
+            .and_then(|record| record.best_seconds);
+        speedrun.recording = GhostTrack::default();
+    }
+    if speedrun.finished {
+        return;
+    }
+    if goal.completed {
+        speedrun.finished = true;
+        if speedrun.show_timer {
+            let best = speedrun.best;
+            speedrun.splits.push(Split {
+                level: stats.level,
+                seconds: stats.elapsed_seconds,
+                best,
+            });
+        }
+        let mut ghost = std::mem::take(&mut speedrun.recording);
+        ghost.seconds = stats.elapsed_seconds;
+        if !stats.used_console {
+            progress.record_ghost(stats.level, ghost);
+        }
+        return;
+    }
+    if speedrun.tick.is_multiple_of(SAMPLE_TICKS) {
+        for (character, transform) in &characters {
+            speedrun
+                .recording
+                .positions
+                .entry(character.clone())
+                .or_default()
+                .push(transform.translation.truncate());
+        }
+    }
+    speedrun.tick += 1;
+}
+
+fn spawn_ghosts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlasses: ResMut<Assets<TextureAtlas>>,
+    progress: Res<Progress>,
+    stats: Query<&LevelStats, Added<LevelStats>>,
+) {
+    let Ok(stats) = stats.get_single() else {
+        return;
+    };
+    let Some(ghost) = progress
+        .get(stats.level)
+        .and_then(|record| record.ghost.as_ref())
+    else {
+        return;
+    };
+    for (character, samples) in &ghost.positions {
+        let Some(start) = samples.first() else {
+            continue;
+        };
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: character.texture(&asset_server, &mut atlasses),
+                sprite: TextureAtlasSprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, GHOST_ALPHA),
+                    ..TextureAtlasSprite::new(0)
+                },
+                transform: Transform::from_xyz(start.x, start.y, 9.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Ghost {
+                character: character.clone(),
+            },
+            LoadedLevel { level: stats.level },
+        ));
+    }
+}
+
+fn move_ghosts(
+    speedrun: Res<Speedrun>,
+    progress: Res<Progress>,
+    stats: Query<&LevelStats>,
+    mut ghosts: Query<(&Ghost, &mut Transform, &mut Visibility)>,
+) {
+    let Ok(stats) = stats.get_single() else {
+        return;
+    };
+    let Some(track) = progress
+        .get(stats.level)
+        .and_then(|record| record.ghost.as_ref())
+    else {
+        return;
+    };
+    for (ghost, mut transform, mut visibility) in &mut ghosts {
+        if let Some(position) = track.position(&ghost.character, speedrun.tick) {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+        *visibility = if speedrun.show_ghost {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn create_timer_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/NotoSans-Regular.ttf"),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(14.0),
+                right: Val::Px(16.0),
+                display: Display::None,
+                ..default()
+            },
+            ..default()
+        },
+        SpeedrunText,
+    ));
+}
+
+fn remove_timer_text(mut commands: Commands, query: Query<Entity, With<SpeedrunText>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_timer_text(
+    speedrun: Res<Speedrun>,
+    progress: Res<Progress>,
+    stats: Query<&LevelStats>,
+    mut text: Query<(&mut Text, &mut Style), With<SpeedrunText>>,
+) {
+    let Ok((mut text, mut style)) = text.get_single_mut() else {
+        return;
+    };
+    style.display = if speedrun.show_timer {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    let Ok(stats) = stats.get_single() else {
+        return;
+    };
+    if !speedrun.show_timer {
+        return;
+    }
+    let mut lines = vec![format!("{:?} {:.2}s", stats.level, stats.elapsed_seconds)];
+    if let Some(best) = progress
+        .get(stats.level)
+        .and_then(|record| record.best_seconds)
+    {
+        lines.push(format!("Best {:.2}s", best));
+    }
+    for split in &speedrun.splits {
+        lines.push(match split.best {
+            Some(best) => format!(
+                "{:?} {:.2}s ({:+.2})",
+                split.level,
+                split.seconds,
+                split.seconds - best
+            ),
+            None => format!("{:?} {:.2}s", split.level, split.seconds),
+        });
+    }
+    if !speedrun.splits.is_empty() {
+        let total: f32 = speedrun.splits.iter().map(|split| split.seconds).sum();
+        lines.push(format!("Total {:.2}s", total));
+    }
+    text.sections[0].value = lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track() -> GhostTrack {
+        GhostTrack {
+            seconds: 12.5,
+            positions: HashMap::from_iter([
+                (
+                    Character::Turtle,
+                    vec![Vec2::new(0.0, 0.0), Vec2::new(12.0, -6.0)],
+                ),
+                (Character::Lizard, vec![Vec2::new(544.5, -608.25)]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let track = track();
+        assert_eq!(GhostTrack::from_json(&track.to_json()), Some(track));
+    }
+
+    #[test]
+    fn positions_are_interpolated_between_samples() {
+        let track = track();
+        let turtle = |tick| track.position(&Character::Turtle, tick);
+        assert_eq!(turtle(0), Some(Vec2::new(0.0, 0.0)));
+        assert_eq!(turtle(SAMPLE_TICKS / 2), Some(Vec2::new(6.0, -3.0)));
+        assert_eq!(turtle(SAMPLE_TICKS), Some(Vec2::new(12.0, -6.0)));
+        // the ghost stays at its last sample once the track ends
+        assert_eq!(turtle(SAMPLE_TICKS * 10), Some(Vec2::new(12.0, -6.0)));
+        assert_eq!(
+            track.position(&Character::Lizard, 3),
+            Some(Vec2::new(544.5, -608.25))
+        );
+        assert_eq!(track.position(&Character::Rabbit, 0), None);
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{physics::CollisionBox, GameState};
+
+use super::{
+    character::Character,
+    level::Teleporter,
+    navigation::NavigationPath,
+    signal::{update_signals, SignalNetwork},
+};
+
+const TELEPORT_COOLDOWN_SECONDS: f32 = 0.5;
+const UNPOWERED_ALPHA: f32 = 0.35;
+
+#[derive(Component)]
+pub struct TeleportCooldown {
+    remaining_seconds: f32,
+}
+
+#[derive(Event)]
+pub struct Teleported {
+    pub character: Character,
+    pub offset: Vec2,
+}
+
+pub struct TeleportPlugin;
+
+impl Plugin for TeleportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Teleported>();
+        app.add_systems(
+            FixedUpdate,
+            (power_teleporters, teleport_characters)
+                .chain()
+                .after(update_signals)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn power_teleporters(
+    network: Query<&SignalNetwork>,
+    mut pads: Query<(&mut Teleporter, &mut Sprite)>,
+) {
+    let Ok(network) = network.get_single() else {
+        return;
+    };
+    for (mut pad, mut sprite) in &mut pads {
+        let powered = pad
+            .channel
+            .as_ref()
+            .map_or(true, |channel| network.get(channel));
+        if pad.powered != powered {
+            pad.powered = powered;
+            sprite
+                .color
+                .set_a(if powered { 1.0 } else { UNPOWERED_ALPHA });
+            info!("Teleporter pair {} is now powered={}", pad.pair, powered);
+        }
+    }
+}
+
+fn teleport_characters(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<FixedTime>,
+    mut teleported: EventWriter<Teleported>,
+    pads: Query<(Entity, &Teleporter, &CollisionBox, &Transform), Without<Character>>,
+    mut characters: Query<(
+        Entity,
+        &Character,
+        &CollisionBox,
+        &mut Transform,
+        &mut NavigationPath,
+        Option<&mut TeleportCooldown>,
+    )>,
+) {
+    for (entity, character, body_box, mut transform, mut path, cooldown) in &mut characters {
+        let body_collider = body_box.to_collider(transform.translation.x, transform.translation.y);
+        let on_pad = pads.iter().find(|(_, _, pad_box, pad_trafo)| {
+            pad_box
+                .to_collider(pad_trafo.translation.x, pad_trafo.translation.y)
+                .does_collide(&body_collider)
+        });
+        if let Some(mut cooldown) = cooldown {
+            cooldown.remaining_seconds -= time.period.as_secs_f32();
+            // standing still on the arrival pad must not send the character back
+            if cooldown.remaining_seconds <= 0.0 && on_pad.is_none() {
+                commands.entity(entity).remove::<TeleportCooldown>();
+            }
+            continue;
+        }
+        let Some((pad_entity, pad, _, _)) = on_pad else {
+            continue;
+        };
+        let partner = pads.iter().find(|(other_entity, other, _, _)| {
+            *other_entity != pad_entity && other.pair == pad.pair
+        });
+        let Some((_, partner, _, partner_trafo)) = partner else {
+            continue;
+        };
+        if !pad.powered || !partner.powered {
+            continue;
+        }
+        let from = transform.translation.truncate();
+        let to = partner_trafo.translation.truncate();
+        transform.translation.x = to.x;
+        transform.translation.y = to.y;
+        path.waypoints.clear();
+        commands.entity(entity).insert(TeleportCooldown {
+            remaining_seconds: TELEPORT_COOLDOWN_SECONDS,
+        });
+        teleported.send(Teleported {
+            character: character.clone(),
+            offset: to - from,
+        });
+        commands.spawn(AudioBundle {
+            source: asset_server.load("sounds/switch_camera.ogg"),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new_absolute(1.0),
+                speed: 1.5,
+                paused: false,
+            },
+        });
+        info!("{:?} teleported from {:?} to {:?}", character, from, to);
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::tilemap::Tilemap;
+
+use super::{
+    character::Character,
+    level_mgr::{HintTarget, LevelData, SolidColliderData, TILE_SIZE},
+    signal::index_channel,
+};
+
+#[derive(Debug)]
+pub struct LevelIssue {
+    pub position: Vec2,
+    pub message: String,
+}
+
+impl LevelIssue {
+    fn new(position: Vec2, message: String) -> LevelIssue {
+        LevelIssue { position, message }
+    }
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at tile ({}, {})",
+            self.message,
+            self.position.x / TILE_SIZE,
+            self.position.y / TILE_SIZE
+        )
+    }
+}
+
+fn is_on_map(ground: &Tilemap, position: Vec2) -> bool {
+    let x = (position.x / TILE_SIZE).round();
+    let y = (position.y / TILE_SIZE).round();
+    x >= 0.0
+        && y >= 0.0
+        && (x as usize) < ground.width()
+        && (y as usize) < ground.height()
+        && ground.has_tile(x as usize, y as usize)
+}
+
+fn covers(collider: &SolidColliderData, position: Vec2) -> bool {
+    let min = collider.corner_position - TILE_SIZE / 2.0;
+    let max = min + collider.size;
+    min.x <= position.x && position.x <= max.x && min.y <= position.y && position.y <= max.y
+}
+
+// solids whitelisted for someone only count when checking for somebody else
+fn blocking_solid<'a>(
+    data: &'a LevelData,
+    position: Vec2,
+    character: Option<&Character>,
+) -> Option<&'a SolidColliderData> {
+    data.map_colliders.iter().find(|collider| {
+        let blocks = match &collider.whitelisted {
+            None => true,
+            Some(whitelisted) => character.is_some_and(|character| character != whitelisted),
+        };
+        blocks && covers(collider, position)
+    })
+}
+
+pub fn validate_level(data: &LevelData, ground: &Tilemap) -> Vec<LevelIssue> {
+    let mut issues = Vec::new();
+    let driven: Vec<String> = data
+        .buttons
+        .iter()
+        .map(|button| index_channel(button.index))
+        .chain(data.signals.iter().flat_map(|node| node.outputs.clone()))
+        .collect();
+    let listening: Vec<String> = data
+        .bridges
+        .iter()
+        .map(|bridge| index_channel(bridge.index))
+        .chain(
+            data.actuators
+                .iter()
+                .map(|actuator| actuator.channel.clone()),
+        )
+        .chain(
+            data.teleporters
+                .iter()
+                .filter_map(|teleporter| teleporter.channel.clone()),
+        )
+        .chain(data.signals.iter().flat_map(|node| node.inputs.clone()))
+        .collect();
+
+    for bridge in &data.bridges {
+        if !driven.contains(&index_channel(bridge.index)) {
+            issues.push(LevelIssue::new(
+                bridge.position,
+                format!("Bridge index {} is not driven by any button", bridge.index),
+            ));
+        }
+        for button in data
+            .buttons
+            .iter()
+            .filter(|button| button.index == bridge.index && button.color != bridge.color)
+        {
+            issues.push(LevelIssue::new(
+                bridge.position,
+                format!(
+                    "Bridge colour differs from the button at tile ({}, {}) with index {}",
+                    button.position.x / TILE_SIZE,
+                    button.position.y / TILE_SIZE,
+                    bridge.index
+                ),
+            ));
+        }
+    }
+    for actuator in &data.actuators {
+        if !driven.contains(&actuator.channel) {
+            issues.push(LevelIssue::new(
+                actuator.position,
+                format!(
+                    "Actuator channel {:?} is not driven by anything",
+                    actuator.channel
+                ),
+            ));
+        }
+    }
+    for button in &data.buttons {
+        if !listening.contains(&index_channel(button.index)) {
+            issues.push(LevelIssue::new(
+                button.position,
+                format!("Nothing listens to button index {}", button.index),
+            ));
+        }
+        if !is_on_map(ground, button.position) {
+            issues.push(LevelIssue::new(
+                button.position,
+                "Button is off the map".to_string(),
+            ));
+        }
+    }
+
+    for character_data in &data.characters {
+        let position = character_data.starting_position;
+        if !is_on_map(ground, position) {
+            issues.push(LevelIssue::new(
+                position,
+                format!("{:?} starts off the map", character_data.character),
+            ));
+        }
+        if let Some(solid) = blocking_solid(data, position, Some(&character_data.character)) {
+            issues.push(LevelIssue::new(
+                position,
+                format!(
+                    "{:?} starts inside the solid at tile ({}, {})",
+                    character_data.character,
+                    solid.corner_position.x / TILE_SIZE,
+                    solid.corner_position.y / TILE_SIZE
+                ),
+            ));
+        }
+    }
+    let starting = data
+        .characters
+        .iter()
+        .find(|character_data| character_data.character == data.starting_character);
+    if !starting.is_some_and(|character_data| character_data.is_discovered) {
+        issues.push(LevelIssue::new(
+            starting.map_or(Vec2::ZERO, |character_data| {
+                character_data.starting_position
+            }),
+            format!(
+                "Starting character {:?} is not a discovered character",
+                data.starting_character
+            ),
+        ));
+    }
+
+    if !is_on_map(ground, data.flag_position) {
+        issues.push(LevelIssue::new(
+            data.flag_position,
+            "Flag is off the map".to_string(),
+        ));
+    }
+    if let Some(solid) = blocking_solid(data, data.flag_position, None) {
+        issues.push(LevelIssue::new(
+            data.flag_position,
+            format!(
+                "Flag is inside the solid at tile ({}, {})",
+                solid.corner_position.x / TILE_SIZE,
+                solid.corner_position.y / TILE_SIZE
+            ),
+        ));
+    }
+    for flag in &data.character_flags {
+        if !is_on_map(ground, flag.position) {
+            issues.push(LevelIssue::new(
+                flag.position,
+                format!("Flag of {:?} is off the map", flag.character),
+            ));
+        }
+    }
+    for hint in &data.hints {
+        let missing = match &hint.target {
+            Some(HintTarget::Button(index)) => *index >= data.buttons.len(),
+            Some(HintTarget::Bridge(index)) => *index >= data.bridges.len(),
+            Some(HintTarget::Character(character)) => !data
+                .characters
+                .iter()
+                .any(|character_data| character_data.character == *character),
+            None => false,
+        };
+        if missing {
+            issues.push(LevelIssue::new(
+                data.flag_position,
+                format!("Hint {:?} points at a missing {:?}", hint.text, hint.target),
+            ));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        gamelogic::level_mgr::ManagedLevel,
+        tilemap::{TileSet, Tiles},
+    };
+
+    #[test]
+    fn levels_are_consistent() {
+        for level in ManagedLevel::levels() {
+            let data = level.get_data();
+            let tile_set = TileSet::from_path(Path::new("assets").join(&data.tileset)).unwrap();
+            let tiles =
+                Tiles::from_path(Path::new("assets").join(&data.tilemap_layers[0])).unwrap();
+            let ground = Tilemap::new(&tile_set, &tiles).unwrap();
+            let issues: Vec<String> = validate_level(data, &ground)
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect();
+            assert!(
+                issues.is_empty(),
+                "{:?} has {} issues:\n{}",
+                level,
+                issues.len(),
+                issues.join("\n")
+            );
+        }
+    }
+}
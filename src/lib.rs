@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+pub mod assets;
+#[cfg(feature = "dev-tools")]
+pub mod console;
+#[cfg(feature = "dev-tools")]
+pub mod editor;
+pub mod gamelogic;
+pub mod loading;
+pub mod menu;
+pub mod physics;
+pub mod simulation;
+pub mod tilemap;
+pub mod util;
+
+#[derive(States, Debug, Default, Hash, Eq, PartialEq, Clone)]
+pub enum GameState {
+    #[default]
+    Loading,
+    Menu,
+    InGame,
+    Editor,
+}
+
+#[derive(Component)]
+pub struct MainCamera;
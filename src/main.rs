@@ -1,36 +1,25 @@
-use assets::{TileSetAssetLoader, TilesAssetLoader};
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     prelude::*,
     window::WindowResolution,
 };
-use gamelogic::{
-    level_mgr::{LevelManager, LoadedLevel, ManagedLevel},
-    GameLogicPlugins,
+use puzzle_pawz::{
+    assets::{TileSetAssetLoader, TilesAssetLoader},
+    gamelogic::{
+        level_mgr::{level_loading, LevelManager, ManagedLevel},
+        GameLogicPlugins,
+    },
+    loading::LoadingPlugin,
+    menu::MenuPlugin,
+    tilemap::{TileSet, Tiles},
+    GameState, MainCamera,
 };
-use loading::{LoadingPlugin, TilemapAtlas};
-use menu::MenuPlugin;
-use tilemap::{TileSet, Tiles};
-
-mod assets;
-mod gamelogic;
-mod loading;
-mod menu;
-mod physics;
-mod tilemap;
-mod util;
-
-#[derive(States, Debug, Default, Hash, Eq, PartialEq, Clone)]
-enum GameState {
-    #[default]
-    Loading,
-    Menu,
-    InGame,
-}
+#[cfg(feature = "dev-tools")]
+use puzzle_pawz::{console::ConsolePlugin, editor::EditorPlugin};
 
 fn main() {
-    App::new()
-        .add_state::<GameState>()
+    let mut app = App::new();
+    app.add_state::<GameState>()
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(Msaa::Off)
         .add_systems(Startup, setup_base)
@@ -56,13 +45,12 @@ fn main() {
         .add_asset::<Tiles>()
         .add_asset::<TileSet>()
         .init_asset_loader::<TilesAssetLoader>()
-        .init_asset_loader::<TileSetAssetLoader>()
-        .run();
+        .init_asset_loader::<TileSetAssetLoader>();
+    #[cfg(feature = "dev-tools")]
+    app.add_plugins((ConsolePlugin, EditorPlugin));
+    app.run();
 }
 
-#[derive(Component)]
-pub struct MainCamera;
-
 fn setup_base(mut commands: Commands) {
     commands.spawn((
         Camera2dBundle {
@@ -76,31 +64,3 @@ fn setup_base(mut commands: Commands) {
         next: Some(ManagedLevel::Level1),
     });
 }
-
-fn level_loading(
-    level_entities: Query<(Entity, &LoadedLevel)>,
-    asset_server: Res<AssetServer>,
-    tilemap_atlas: Res<TilemapAtlas>,
-    atlasses: ResMut<Assets<TextureAtlas>>,
-    tiles: Res<Assets<Tiles>>,
-    tilesets: Res<Assets<TileSet>>,
-    mut commands: Commands,
-    mut camera: Query<&mut Transform, With<MainCamera>>,
-    mut query: Query<&mut LevelManager, Changed<LevelManager>>,
-) {
-    let mut camera = camera.single_mut();
-    if let Ok(mut manager) = query.get_single_mut() {
-        manager.unload_level(&mut commands, level_entities.iter());
-        manager.load_level(
-            asset_server,
-            tilemap_atlas,
-            atlasses,
-            tiles,
-            tilesets,
-            &mut camera,
-            commands,
-        );
-        manager.current = manager.next;
-        manager.next = None;
-    }
-}
@@ -6,7 +6,7 @@ use bevy::{
     utils::default,
 };
 
-use crate::GameState;
+use crate::{gamelogic::budget::ChallengeMode, GameState};
 
 pub struct MenuPlugin;
 
@@ -21,6 +21,9 @@ impl Plugin for MenuPlugin {
 #[derive(Component, Debug)]
 enum ButtonKinds {
     Start,
+    Challenge,
+    #[cfg(feature = "dev-tools")]
+    Editor,
     Quit,
 }
 
@@ -37,6 +40,27 @@ impl ButtonKinds {
                 },
                 alpha: 1.0,
             },
+            ButtonKinds::Challenge => Color::Hsla {
+                hue: 40.0,
+                saturation: 0.8,
+                lightness: match interaction {
+                    Interaction::Pressed => 0.6,
+                    Interaction::Hovered => 0.5,
+                    Interaction::None => 0.4,
+                },
+                alpha: 1.0,
+            },
+            #[cfg(feature = "dev-tools")]
+            ButtonKinds::Editor => Color::Hsla {
+                hue: 210.0,
+                saturation: 0.6,
+                lightness: match interaction {
+                    Interaction::Pressed => 0.6,
+                    Interaction::Hovered => 0.5,
+                    Interaction::None => 0.4,
+                },
+                alpha: 1.0,
+            },
             ButtonKinds::Quit => Color::Hsla {
                 hue: 5.0,
                 saturation: 0.8,
@@ -55,14 +79,25 @@ fn button_interactions(
     mut query: Query<(&Interaction, &mut BackgroundColor, &ButtonKinds), Changed<Interaction>>,
     mut exit: EventWriter<AppExit>,
     mut state: ResMut<NextState<GameState>>,
+    mut challenge: ResMut<ChallengeMode>,
 ) {
     for (interaction, mut bg_color, button) in &mut query {
         *bg_color = BackgroundColor(button.get_color(interaction));
         match *interaction {
             Interaction::Pressed => match button {
                 ButtonKinds::Start => {
+                    challenge.enabled = false;
                     state.set(GameState::InGame);
                 }
+                ButtonKinds::Challenge => {
+                    challenge.enabled = true;
+                    state.set(GameState::InGame);
+                }
+                #[cfg(feature = "dev-tools")]
+                ButtonKinds::Editor => {
+                    challenge.enabled = false;
+                    state.set(GameState::Editor);
+                }
                 ButtonKinds::Quit => {
                     exit.send(AppExit);
                 }
@@ -133,6 +168,45 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
             .with_children(|p| {
                 p.spawn(TextBundle::from_section("Start", button_text_style.clone()));
             });
+            p.spawn((
+                ButtonBundle {
+                    style: Style {
+                        border: UiRect::axes(Val::Px(5.0), Val::Px(5.0)),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        width: Val::Percent(30.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                ButtonKinds::Challenge,
+            ))
+            .with_children(|p| {
+                p.spawn(TextBundle::from_section(
+                    "Challenge",
+                    button_text_style.clone(),
+                ));
+            });
+            #[cfg(feature = "dev-tools")]
+            p.spawn((
+                ButtonBundle {
+                    style: Style {
+                        border: UiRect::axes(Val::Px(5.0), Val::Px(5.0)),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        width: Val::Percent(30.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                ButtonKinds::Editor,
+            ))
+            .with_children(|p| {
+                p.spawn(TextBundle::from_section(
+                    "Editor",
+                    button_text_style.clone(),
+                ));
+            });
             p.spawn((
                 ButtonBundle {
                     style: Style {
@@ -7,6 +7,11 @@ pub struct Solid {
     pub whitelisted: Option<Character>,
 }
 
+#[derive(Component)]
+pub struct Pushable {
+    pub mass: f32,
+}
+
 #[derive(Component)]
 pub enum CollisionBox {
     Circle {
@@ -29,12 +34,22 @@ impl CollisionBox {
     }
 }
 
+#[derive(Clone)]
 pub enum Collider {
     Circle(Vec2, f32),
     AABB(Vec2, Vec2),
 }
 
 impl Collider {
+    pub fn grown(&self, margin: f32) -> Collider {
+        match self {
+            Collider::Circle(center, radius) => Collider::Circle(*center, radius + margin),
+            Collider::AABB(center, size) => {
+                Collider::AABB(*center, *size + Vec2::new(margin, margin) * 2.0)
+            }
+        }
+    }
+
     pub fn does_collide(&self, other: &Collider) -> bool {
         self.collide(other).is_some()
     }
@@ -43,7 +58,7 @@ impl Collider {
         match (self, other) {
             (Collider::Circle(_, _), Collider::Circle(_, _)) => self.collide_circle_circle(other),
             (Collider::AABB(_, _), Collider::AABB(_, _)) => self.collide_aabb_aabb(other),
-            (_, Collider::Circle(_, _)) => todo!(), //self.collide_circle_aabb(other),
+            (_, Collider::Circle(_, _)) => other.collide_circle_aabb(self).map(|p| -p),
             (_, _) => self.collide_circle_aabb(other),
         }
     }
@@ -58,8 +73,8 @@ impl Collider {
                 let collide_distance = self_radius.abs() + other_radius.abs();
                 if distance <= collide_distance {
                     Some(
-                        (*other_center - *self_center)
-                            * ((self_radius.abs() + other_radius.abs() - distance) / 2.0),
+                        (*other_center - *self_center).normalize_or_zero()
+                            * (collide_distance - distance),
                     )
                 } else {
                     None
@@ -79,7 +94,15 @@ impl Collider {
                     && f_min_y <= s_max_y
                     && s_min_y <= f_max_y
                 {
-                    todo!()
+                    let penetration_x = (f_max_x.min(s_max_x) - f_min_x.max(s_min_x))
+                        * (other_center.x - self_center.x).signum();
+                    let penetration_y = (f_max_y.min(s_max_y) - f_min_y.max(s_min_y))
+                        * (other_center.y - self_center.y).signum();
+                    Some(if penetration_x.abs() < penetration_y.abs() {
+                        Vec2::new(penetration_x, 0.0)
+                    } else {
+                        Vec2::new(0.0, penetration_y)
+                    })
                 } else {
                     None
                 }
@@ -163,6 +186,16 @@ impl Collider {
     }
 }
 
+pub fn total_penetration<I>(collider: &Collider, others: I) -> Vec2
+where
+    I: Iterator<Item = Collider>,
+{
+    others
+        .filter_map(|other| collider.collide(&other))
+        .filter(|penetration| penetration.is_finite())
+        .sum()
+}
+
 fn aabb_bounds(center: &Vec2, size: &Vec2) -> [f32; 4] {
     let min_x = center.x - size.x.abs() / 2.0;
     let min_y = center.y - size.y.abs() / 2.0;
@@ -0,0 +1,383 @@
+use std::path::Path;
+
+use bevy::{
+    asset::AssetPlugin,
+    gizmos::GizmoPlugin,
+    input::{keyboard::KeyboardInput, ButtonState, InputPlugin},
+    prelude::*,
+    render::render_resource::Shader,
+    time::TimeUpdateStrategy,
+};
+
+use crate::{
+    assets::{TileSetAssetLoader, TilesAssetLoader},
+    gamelogic::{
+        actions::Actions,
+        character::Character,
+        level_mgr::{level_loading, LevelManager, ManagedLevel},
+        rating::Progress,
+        replay::{Replay, ReplayState},
+        GameLogicPlugins,
+    },
+    loading::TilemapAtlas,
+    tilemap::{TileSet, Tiles},
+    GameState, MainCamera,
+};
+
+const ASSET_DIR: &str = "assets";
+
+// the game logic of a level without a window or renderer, every update is one tick
+pub struct Simulation {
+    pub app: App,
+    // level files stay loaded so the level can be restarted
+    handles: Vec<HandleUntyped>,
+}
+
+impl Simulation {
+    pub fn new(level: ManagedLevel) -> Simulation {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins((
+                AssetPlugin::default(),
+                InputPlugin,
+                TransformPlugin,
+                HierarchyPlugin,
+            ))
+            .add_asset::<Shader>()
+            .add_asset::<Image>()
+            .add_asset::<TextureAtlas>()
+            .add_asset::<AudioSource>()
+            .add_asset::<Font>()
+            .add_asset::<Tiles>()
+            .add_asset::<TileSet>()
+            .init_asset_loader::<TilesAssetLoader>()
+            .init_asset_loader::<TileSetAssetLoader>()
+            .add_plugins(GizmoPlugin)
+            .add_state::<GameState>()
+            .add_plugins(GameLogicPlugins)
+            .add_systems(Update, level_loading.run_if(in_state(GameState::InGame)));
+        let period = app.world.resource::<FixedTime>().period;
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(period));
+        // the player's save file is neither read nor written
+        app.insert_resource(Progress::default());
+
+        // nothing is drawn, so the tiles don't need any textures
+        let atlas = app
+            .world
+            .resource_mut::<Assets<TextureAtlas>>()
+            .add(TextureAtlas::new_empty(Handle::default(), Vec2::ZERO));
+        app.insert_resource(TilemapAtlas {
+            tilemap: Some(atlas),
+        });
+        let mut simulation = Simulation {
+            app,
+            handles: Vec::new(),
+        };
+        simulation.load_files(level);
+
+        let world = &mut simulation.app.world;
+        world.spawn(Window::default());
+        world.spawn((Camera2dBundle::default(), MainCamera));
+        world.spawn(LevelManager {
+            current: None,
+            next: Some(level),
+        });
+        world.insert_resource(NextState(Some(GameState::InGame)));
+        simulation.app.update();
+        simulation
+    }
+
+    // the asset server would load them in the background, the level needs them right away
+    fn load_files(&mut self, level: ManagedLevel) {
+        let data = level.get_data();
+        let tile_set = TileSet::from_path(Path::new(ASSET_DIR).join(&data.tileset))
+            .unwrap_or_else(|| panic!("Could not load {}", data.tileset));
+        let handle = self
+            .app
+            .world
+            .resource_mut::<Assets<TileSet>>()
+            .set(data.tileset.as_str(), tile_set);
+        self.handles.push(handle.clone_untyped());
+        for layer in &data.tilemap_layers {
+            let tiles = Tiles::from_path(Path::new(ASSET_DIR).join(layer))
+                .unwrap_or_else(|| panic!("Could not load {}", layer));
+            let handle = self
+                .app
+                .world
+                .resource_mut::<Assets<Tiles>>()
+                .set(layer.as_str(), tiles);
+            self.handles.push(handle.clone_untyped());
+        }
+    }
+
+    pub fn step(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    pub fn step_seconds(&mut self, seconds: f32) {
+        let period = self.app.world.resource::<FixedTime>().period;
+        self.step((seconds / period.as_secs_f32()).round() as usize);
+    }
+
+    fn send_key(&mut self, key: KeyCode, state: ButtonState) {
+        self.app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    // held until released
+    pub fn press(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Pressed);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Released);
+    }
+
+    pub fn tap(&mut self, key: KeyCode) {
+        self.press(key);
+        self.step(1);
+        self.release(key);
+    }
+
+    pub fn walk_to(&mut self, target: Vec2) {
+        self.app.world.resource_mut::<Actions>().pending.walk_to = Some(target);
+    }
+
+    pub fn play(&mut self, replay: Replay) {
+        self.app
+            .world
+            .resource_scope(|world, mut state: Mut<ReplayState>| {
+                let mut manager = world.query::<&mut LevelManager>();
+                state.play(replay, &mut manager.single_mut(world));
+            });
+    }
+
+    pub fn level(&mut self) -> Option<ManagedLevel> {
+        let mut manager = self.app.world.query::<&LevelManager>();
+        manager.single(&self.app.world).current
+    }
+
+    pub fn position(&mut self, character: Character) -> Vec2 {
+        let mut characters = self.app.world.query::<(&Character, &Transform)>();
+        characters
+            .iter(&self.app.world)
+            .find(|(c, _)| **c == character)
+            .map(|(_, transform)| transform.translation.truncate())
+            .unwrap_or_else(|| panic!("{:?} is not in the level", character))
+    }
+
+    pub fn set_position(&mut self, character: Character, position: Vec2) {
+        let mut characters = self.app.world.query::<(&Character, &mut Transform)>();
+        for (c, mut transform) in characters.iter_mut(&mut self.app.world) {
+            if *c == character {
+                transform.translation.x = position.x;
+                transform.translation.y = position.y;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gamelogic::{
+            actions::PlayerActions,
+            hint::LevelHints,
+            level::{
+                Actuator, ActuatorBundle, ActuatorKind, GatedBridge, Hazard, HazardBundle,
+                HazardKind,
+            },
+            level_mgr::{HintTarget, TILE_SIZE},
+            speedrun::Speedrun,
+        },
+        physics::{CollisionBox, Solid},
+    };
+
+    fn bridge_is_open(simulation: &mut Simulation, index: usize) -> bool {
+        let mut bridges = simulation
+            .app
+            .world
+            .query::<(&GatedBridge, &Actuator, Option<&Solid>)>();
+        bridges
+            .iter(&simulation.app.world)
+            .filter(|(bridge, _, _)| bridge.index == index)
+            .all(|(_, actuator, solid)| actuator.is_open() && solid.is_none())
+    }
+
+    fn spawn_chasm(simulation: &mut Simulation, position: Vec2) {
+        simulation.app.world.spawn(HazardBundle {
+            hazard: Hazard {
+                kind: HazardKind::Chasm,
+            },
+            collision: CollisionBox::AABB {
+                width_radius: TILE_SIZE,
+                height_radius: TILE_SIZE,
+            },
+            spatial: SpatialBundle::from_transform(Transform::from_translation(
+                position.extend(3.0),
+            )),
+        });
+    }
+
+    fn spawn_platform(simulation: &mut Simulation, position: Vec2) {
+        simulation.app.world.spawn(ActuatorBundle {
+            actuator: Actuator::new(
+                ActuatorKind::Platform { offset: Vec2::ZERO },
+                "platform".to_string(),
+                false,
+                position.extend(3.0),
+            ),
+            collision: CollisionBox::AABB {
+                width_radius: TILE_SIZE,
+                height_radius: TILE_SIZE,
+            },
+            sprite: SpriteBundle {
+                transform: Transform::from_translation(position.extend(3.0)),
+                ..default()
+            },
+        });
+    }
+
+    fn finish_level(simulation: &mut Simulation, level: ManagedLevel) {
+        let flag = level.get_data().flag_position;
+        simulation.set_position(Character::Turtle, Vec2::new(flag.x, -flag.y));
+        simulation.step(2);
+    }
+
+    #[test]
+    fn finished_levels_are_only_recorded_in_memory() {
+        let mut simulation = Simulation::new(ManagedLevel::Level1);
+        simulation.step(1);
+        finish_level(&mut simulation, ManagedLevel::Level1);
+        let progress = simulation.app.world.resource::<Progress>();
+        assert!(progress.path.is_none());
+        assert!(progress
+            .get(ManagedLevel::Level1)
+            .is_some_and(|record| record.best_seconds.is_some()));
+    }
+
+    #[test]
+    fn splits_compare_against_the_best_time_before_the_run() {
+        let mut simulation = Simulation::new(ManagedLevel::Level1);
+        simulation.app.world.resource_mut::<Speedrun>().show_timer = true;
+        simulation.step(1);
+        finish_level(&mut simulation, ManagedLevel::Level1);
+        let mut manager = simulation.app.world.query::<&mut LevelManager>();
+        manager.single_mut(&mut simulation.app.world).next = Some(ManagedLevel::Level1);
+        simulation.step(60);
+        finish_level(&mut simulation, ManagedLevel::Level1);
+        let splits = &simulation.app.world.resource::<Speedrun>().splits;
+        assert_eq!(splits.len(), 2);
+        assert_eq!(splits[0].best, None);
+        assert_eq!(splits[1].best, Some(splits[0].seconds));
+    }
+
+    #[test]
+    fn holding_a_key_walks_the_current_character() {
+        let mut simulation = Simulation::new(ManagedLevel::Level1);
+        let start = simulation.position(Character::Turtle);
+        simulation.press(KeyCode::S);
+        simulation.step_seconds(1.0);
+        let position = simulation.position(Character::Turtle);
+        assert_eq!(position.x, start.x);
+        let walked = start.y - position.y;
+        assert!(
+            (walked - Character::Turtle.speed()).abs() <= 1.0,
+            "walked {} instead of {}",
+            walked,
+            Character::Turtle.speed()
+        );
+    }
+
+    #[test]
+    fn walls_stop_the_turtle() {
+        let mut simulation = Simulation::new(ManagedLevel::Level1);
+        let start = simulation.position(Character::Turtle);
+        simulation.press(KeyCode::D);
+        simulation.step_seconds(2.0);
+        let position = simulation.position(Character::Turtle);
+        assert!(position.x > start.x);
+        assert!(position.x < 20.2 * TILE_SIZE, "walked through the wall");
+        assert!(position.x - start.x < 2.0 * Character::Turtle.speed() - TILE_SIZE);
+    }
+
+    #[test]
+    fn rabbit_on_button_opens_bridge() {
+        let mut simulation = Simulation::new(ManagedLevel::Level2);
+        simulation.step(1);
+        assert!(!bridge_is_open(&mut simulation, 0));
+        simulation.set_position(Character::Rabbit, Vec2::new(22.0, -21.0) * TILE_SIZE);
+        simulation.step_seconds(1.0);
+        assert!(bridge_is_open(&mut simulation, 0));
+        simulation.set_position(Character::Rabbit, Vec2::new(18.0, -20.0) * TILE_SIZE);
+        simulation.step_seconds(1.0);
+        assert!(!bridge_is_open(&mut simulation, 0));
+    }
+
+    #[test]
+    fn replays_drive_the_characters() {
+        let mut simulation = Simulation::new(ManagedLevel::Level1);
+        let start = simulation.position(Character::Turtle);
+        let mut replay = Replay::new(ManagedLevel::Level1);
+        replay.ticks = vec![
+            PlayerActions {
+                movement: Vec2::new(0.0, -1.0),
+                ..default()
+            };
+            60
+        ];
+        simulation.play(replay);
+        simulation.step(70);
+        assert_eq!(simulation.level(), Some(ManagedLevel::Level1));
+        let walked = start.y - simulation.position(Character::Turtle).y;
+        assert!((walked - Character::Turtle.speed()).abs() <= 1.0);
+    }
+
+    #[test]
+    fn hint_key_reveals_one_hint_at_a_time() {
+        let mut simulation = Simulation::new(ManagedLevel::Level3);
+        let mut hints = simulation.app.world.query::<&LevelHints>();
+        assert!(hints.single(&simulation.app.world).current().is_none());
+        simulation.tap(KeyCode::H);
+        simulation.step(1);
+        let current = hints.single(&simulation.app.world).current().cloned();
+        assert_eq!(
+            current.and_then(|hint| hint.target),
+            Some(HintTarget::Button(0))
+        );
+        for _ in 0..10 {
+            simulation.tap(KeyCode::H);
+        }
+        let hints = hints.single(&simulation.app.world);
+        assert_eq!(hints.revealed, hints.hints.len());
+    }
+
+    #[test]
+    fn characters_respawn_in_chasms() {
+        let mut simulation = Simulation::new(ManagedLevel::Level1);
+        let start = simulation.position(Character::Turtle);
+        let chasm = Vec2::new(18.0, -19.0) * TILE_SIZE;
+        spawn_chasm(&mut simulation, chasm);
+        simulation.set_position(Character::Turtle, chasm);
+        simulation.step(2);
+        assert_eq!(simulation.position(Character::Turtle), start);
+    }
+
+    #[test]
+    fn platforms_are_ground_over_chasms() {
+        let mut simulation = Simulation::new(ManagedLevel::Level1);
+        let chasm = Vec2::new(18.0, -19.0) * TILE_SIZE;
+        spawn_chasm(&mut simulation, chasm);
+        spawn_platform(&mut simulation, chasm);
+        simulation.set_position(Character::Turtle, chasm);
+        simulation.step(2);
+        assert_eq!(simulation.position(Character::Turtle), chasm);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fs::File, path::Path};
 
 use crate::loading::TilemapAtlas;
 use bevy::{
@@ -11,6 +11,7 @@ use bevy::{
 #[uuid = "9ebbbcc1-0fc9-4c4f-841c-21b137bb0173"]
 pub struct TileSet {
     texture_names: BTreeMap<usize, String>, // registry names of tiles
+    properties: BTreeMap<usize, BTreeMap<String, String>>, // custom properties set in Tiled
 }
 
 impl TileSet {
@@ -31,11 +32,57 @@ impl TileSet {
                 })
                 .collect()
         })?;
+        let properties = tileset["tiles"]
+            .as_array()
+            .map(|tiles| {
+                tiles
+                    .iter()
+                    .filter_map(|tile| {
+                        let index = tile["id"].as_u64()? as usize;
+                        let properties = tile["properties"]
+                            .as_array()?
+                            .iter()
+                            .filter_map(|property| {
+                                let name = property["name"].as_str()?.to_string();
+                                let value = match &property["value"] {
+                                    serde_json::Value::String(value) => value.clone(),
+                                    value => value.to_string(),
+                                };
+                                Some((name, value))
+                            })
+                            .collect();
+                        Some((index, properties))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
         Some(TileSet {
             texture_names: texture_names,
+            properties,
         })
     }
 
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<TileSet> {
+        TileSet::from_reader(File::open(path).ok()?)
+    }
+
+    pub fn texture_names(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.texture_names
+            .iter()
+            .map(|(tile_type, name)| (*tile_type, name.as_str()))
+    }
+
+    fn get_property(&self, tile_type: isize, name: &str) -> Option<&str> {
+        if tile_type < 0 {
+            None
+        } else {
+            self.properties
+                .get(&(tile_type as usize))
+                .and_then(|properties| properties.get(name))
+                .map(|s| s.as_str())
+        }
+    }
+
     fn get_texture_name(&self, tile_type: isize) -> Option<&str> {
         if tile_type < 0 {
             None
@@ -47,7 +94,7 @@ impl TileSet {
     }
 }
 
-#[derive(TypeUuid, TypePath)]
+#[derive(TypeUuid, TypePath, Clone)]
 #[uuid = "4b4a05b4-bfe8-4b1e-9561-e04545132cc3"]
 pub struct Tiles {
     tiles: Vec<Vec<isize>>,
@@ -67,6 +114,42 @@ impl Tiles {
             .collect::<Option<Vec<Vec<isize>>>>()?;
         Some(Tiles { tiles })
     }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Tiles> {
+        Tiles::from_reader(File::open(path).ok()?)
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles.iter().map(|row| row.len()).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<usize> {
+        self.tiles
+            .get(y)
+            .and_then(|row| row.get(x))
+            .filter(|tile_type| **tile_type >= 0)
+            .map(|tile_type| *tile_type as usize)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, tile: Option<usize>) {
+        if let Some(tile_type) = self.tiles.get_mut(y).and_then(|row| row.get_mut(x)) {
+            *tile_type = tile.map_or(-1, |tile| tile as isize);
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        self.tiles
+            .iter()
+            .map(|row| {
+                let row: Vec<String> = row.iter().map(|tile_type| tile_type.to_string()).collect();
+                row.join(",") + "\n"
+            })
+            .collect()
+    }
 }
 
 pub struct Tilemap<'tileset> {
@@ -96,7 +179,20 @@ impl<'tileset> Tilemap<'tileset> {
         self.height
     }
 
-    fn get_texture_name_of_tile(&self, x: usize, y: usize) -> Option<&str> {
+    pub fn has_tile(&self, x: usize, y: usize) -> bool {
+        self.tiles
+            .get(x + self.width() * y)
+            .is_some_and(|tile_type| *tile_type >= 0)
+    }
+
+    pub fn get_property_of_tile(&self, x: usize, y: usize, name: &str) -> Option<&str> {
+        let tile_index = x + self.width() * y;
+        self.tiles
+            .get(tile_index)
+            .and_then(|tile_type| self.tile_set.get_property(*tile_type, name))
+    }
+
+    pub fn get_texture_name_of_tile(&self, x: usize, y: usize) -> Option<&str> {
         let tile_index = x + self.width() * y;
         self.tiles
             .get(tile_index)